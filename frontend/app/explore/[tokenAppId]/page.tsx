@@ -26,6 +26,10 @@ const LS_WALLET = 'linad_wallet_address';
 const TEXT_ENCODER = new TextEncoder();
 const AMOUNT_DECIMALS = 18n;
 const TRADE_TYPE = 'TradeRequest';
+// wLin tip for the relaying node; the UI submits through its own node, so no tip.
+const TRADE_RELAYER_FEE = '0';
+// Signed trades expire so a withheld signature cannot be replayed later.
+const TRADE_DEADLINE_MS = 10 * 60 * 1000;
 const APPROVE_TYPE = 'ApproveRequest';
 
 function parseGraphqlAmountToAttos(value: unknown): bigint | null {
@@ -159,6 +163,16 @@ function encodeVariantIndex(index: number) {
   return new Uint8Array([index]);
 }
 
+function encodeU64LE(value: bigint) {
+  const bytes = new Uint8Array(8);
+  let cursor = value;
+  for (let i = 0; i < 8; i += 1) {
+    bytes[i] = Number(cursor & 0xffn);
+    cursor >>= 8n;
+  }
+  return bytes;
+}
+
 function encodeU128LE(value: bigint) {
   const bytes = new Uint8Array(16);
   let cursor = value;
@@ -209,7 +223,16 @@ function encodeAccountOwner(owner: string) {
   throw new Error('Owner must be 20-byte or 32-byte hex');
 }
 
-function encodeTradeRequest(payload: { owner: string; symbol: string; side: 'BUY' | 'SELL'; amount: string; minOut: string }) {
+function encodeTradeRequest(payload: {
+  owner: string;
+  symbol: string;
+  side: 'BUY' | 'SELL';
+  amount: string;
+  minOut: string;
+  relayerFee: string;
+  deadlineMicros: bigint;
+  nonce: bigint;
+}) {
   // Side enum (BCS variant index): Buy=0, Sell=1
   const sideIndex = payload.side === 'BUY' ? 0 : 1;
   return concatBytes(
@@ -217,7 +240,11 @@ function encodeTradeRequest(payload: { owner: string; symbol: string; side: 'BUY
     encodeString(payload.symbol),
     encodeVariantIndex(sideIndex),
     encodeAmount(payload.amount),
-    encodeAmount(payload.minOut)
+    encodeAmount(payload.minOut),
+    encodeAmount(payload.relayerFee),
+    // Timestamp is BCS-encoded as u64 microseconds.
+    encodeU64LE(payload.deadlineMicros),
+    encodeU64LE(payload.nonce)
   );
 }
 
@@ -267,6 +294,27 @@ async function appendPoint(tokenAppId: string, point: PricePoint) {
   }
 }
 
+async function fetchTradeNonce(owner: string): Promise<bigint> {
+  const endpoint = DEFAULT_GRAPHQL_ENDPOINT.replace(/\/$/, '');
+  const url = `${endpoint}/chains/${DEFAULT_CHAIN_ID}/applications/${DEFAULT_MATCHING_ENGINE_APP_ID}`;
+  const response = await fetch(url, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({
+      query: `query TradeNonce($owner: String!) {
+        tradeNonce(owner: $owner)
+      }`,
+      variables: { owner },
+    }),
+  });
+  const json = await response.json();
+  const nonce = json?.data?.tradeNonce;
+  if (typeof nonce !== 'number' && typeof nonce !== 'string') {
+    throw new Error('Failed to fetch trade nonce.');
+  }
+  return BigInt(nonce);
+}
+
 async function fetchSpotPrice(symbol: string): Promise<{ price: number; debug: any } | null> {
   const endpoint = DEFAULT_GRAPHQL_ENDPOINT.replace(/\/$/, '');
   const url = `${endpoint}/chains/${DEFAULT_CHAIN_ID}/applications/${DEFAULT_MATCHING_ENGINE_APP_ID}`;
@@ -829,7 +877,14 @@ export default function TokenDetailsPage() {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [tokenAppId, token?.symbol]);
 
-  async function signTradeWithMetaMask(params: { symbol: string; side: 'BUY' | 'SELL'; amount: string; minOut: string }) {
+  async function signTradeWithMetaMask(params: {
+    symbol: string;
+    side: 'BUY' | 'SELL';
+    amount: string;
+    minOut: string;
+    deadlineMicros: bigint;
+    nonce: bigint;
+  }) {
     const connected = walletAddress.trim();
     if (!connected) {
       throw new Error('Connect MetaMask first.');
@@ -848,6 +903,9 @@ export default function TokenDetailsPage() {
       side: params.side,
       amount: params.amount,
       minOut: params.minOut,
+      relayerFee: TRADE_RELAYER_FEE,
+      deadlineMicros: params.deadlineMicros,
+      nonce: params.nonce,
     });
 
     const domain = TEXT_ENCODER.encode(`${TRADE_TYPE}::`);
@@ -945,19 +1003,23 @@ export default function TokenDetailsPage() {
       await approveIfNeeded({ appId: tokenAppId, allowance });
     }
 
+    const nonce = await fetchTradeNonce(walletAddress);
+    const deadlineMicros = BigInt(Date.now() + TRADE_DEADLINE_MS) * 1000n;
     const signatureHex = await signTradeWithMetaMask({
       symbol: token.symbol,
       side: params.side,
       amount: params.amount,
       minOut: params.minOut,
+      deadlineMicros,
+      nonce,
     });
 
     const endpoint = DEFAULT_GRAPHQL_ENDPOINT.replace(/\/$/, '');
     const url = `${endpoint}/chains/${DEFAULT_CHAIN_ID}/applications/${DEFAULT_MATCHING_ENGINE_APP_ID}`;
     const mutation = params.side === 'BUY' ? 'buy' : 'sell';
     const mutationBody = {
-      query: `mutation Trade($owner: String!, $symbol: String!, $side: Side!, $amount: String!, $minOut: String!, $sig: String!) {
-        ${mutation}(trade: { payload: { owner: $owner, symbol: $symbol, side: $side, amount: $amount, minOut: $minOut }, signatureHex: $sig })
+      query: `mutation Trade($owner: String!, $symbol: String!, $side: Side!, $amount: String!, $minOut: String!, $relayerFee: String!, $deadline: Timestamp!, $nonce: Int!, $sig: String!) {
+        ${mutation}(trade: { payload: { owner: $owner, symbol: $symbol, side: $side, amount: $amount, minOut: $minOut, relayerFee: $relayerFee, deadline: $deadline, nonce: $nonce }, signatureHex: $sig })
       }`,
       variables: {
         owner: walletAddress,
@@ -965,6 +1027,9 @@ export default function TokenDetailsPage() {
         side: params.side,
         amount: params.amount,
         minOut: params.minOut,
+        relayerFee: TRADE_RELAYER_FEE,
+        deadline: Number(deadlineMicros),
+        nonce: Number(nonce),
         sig: signatureHex,
       },
     };
@@ -1,6 +1,7 @@
 'use client';
 
 import { useEffect, useRef, useState } from 'react';
+import { keccak_256 } from '@noble/hashes/sha3';
 
 const defaultForm = {
   amount: '1000',
@@ -11,6 +12,121 @@ const defaultForm = {
   endpoint: 'http://127.0.0.1:8080'
 };
 
+const TEXT_ENCODER = new TextEncoder();
+const AMOUNT_DECIMALS = 18n;
+const FAUCET_MINT_TYPE = 'FaucetMintRequest';
+
+function concatBytes(...chunks: Uint8Array[]) {
+  const length = chunks.reduce((sum, chunk) => sum + chunk.length, 0);
+  const result = new Uint8Array(length);
+  let offset = 0;
+  for (const chunk of chunks) {
+    result.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return result;
+}
+
+function bytesToHex(bytes: Uint8Array) {
+  return Array.from(bytes)
+    .map((value) => value.toString(16).padStart(2, '0'))
+    .join('');
+}
+
+function hexToBytes(hex: string) {
+  const normalized = hex.replace(/^0x/i, '');
+  if (normalized.length % 2 !== 0) {
+    throw new Error('Invalid hex string length');
+  }
+  const bytes = new Uint8Array(normalized.length / 2);
+  for (let i = 0; i < bytes.length; i += 1) {
+    bytes[i] = Number.parseInt(normalized.slice(i * 2, i * 2 + 2), 16);
+  }
+  return bytes;
+}
+
+function encodeU128LE(value: bigint) {
+  const bytes = new Uint8Array(16);
+  let cursor = value;
+  for (let i = 0; i < 16; i += 1) {
+    bytes[i] = Number(cursor & 0xffn);
+    cursor >>= 8n;
+  }
+  return bytes;
+}
+
+function parseAmountToU128(input: string) {
+  const raw = input.trim().replace(/_/g, '');
+  if (!raw) {
+    return 0n;
+  }
+  if (raw.startsWith('-')) {
+    throw new Error('Amount cannot be negative');
+  }
+  const [integerPartRaw, fractionalRaw = ''] = raw.replace(/^\+/, '').split('.');
+  const integerPart = integerPartRaw || '0';
+  if (fractionalRaw.length > Number(AMOUNT_DECIMALS)) {
+    throw new Error('Too many decimal places for Amount');
+  }
+  const fractionalPart = fractionalRaw.padEnd(Number(AMOUNT_DECIMALS), '0');
+  const digits = `${integerPart}${fractionalPart}`.replace(/^0+/, '') || '0';
+  return BigInt(digits);
+}
+
+function encodeAccountOwner(owner: string) {
+  const normalized = owner.trim().toLowerCase().replace(/^0x/, '');
+  if (normalized.length === 40) {
+    // AccountOwner::Address20
+    return concatBytes(new Uint8Array([2]), hexToBytes(normalized));
+  }
+  if (normalized.length === 64) {
+    // AccountOwner::Address32
+    return concatBytes(new Uint8Array([1]), hexToBytes(normalized));
+  }
+  throw new Error('Owner must be 20-byte or 32-byte hex');
+}
+
+function encodeFaucetMintRequest(payload: { owner: string; amount: string }) {
+  return concatBytes(encodeAccountOwner(payload.owner), encodeU128LE(parseAmountToU128(payload.amount)));
+}
+
+function encodeEvmAccountSignature(signatureHex: string, address: string) {
+  const sigBytes = hexToBytes(signatureHex);
+  if (sigBytes.length !== 65) {
+    throw new Error('EVM signature must be 65 bytes');
+  }
+  const v = sigBytes[64];
+  if (v === 0 || v === 1) {
+    sigBytes[64] = v + 27;
+  }
+  const addressBytes = hexToBytes(address.trim().toLowerCase().replace(/^0x/, ''));
+  if (addressBytes.length !== 20) {
+    throw new Error('EVM address must be 20 bytes');
+  }
+  // AccountSignature::EvmSecp256k1
+  return concatBytes(new Uint8Array([2]), sigBytes, addressBytes);
+}
+
+async function signFaucetMintWithMetaMask(payload: { owner: string; amount: string }) {
+  const ethereum = (window as Window & { ethereum?: any }).ethereum;
+  if (!ethereum) {
+    throw new Error('MetaMask not detected.');
+  }
+  const accounts: string[] = await ethereum.request({ method: 'eth_requestAccounts' });
+  const connected = accounts?.[0] ?? '';
+  if (connected.toLowerCase() !== payload.owner.toLowerCase()) {
+    throw new Error('Owner must match connected MetaMask address.');
+  }
+
+  const domain = TEXT_ENCODER.encode(`${FAUCET_MINT_TYPE}::`);
+  const hash = keccak_256(concatBytes(domain, encodeFaucetMintRequest(payload)));
+  const rawSignature: string = await ethereum.request({
+    method: 'personal_sign',
+    params: [`0x${bytesToHex(hash)}`, connected],
+  });
+  return bytesToHex(encodeEvmAccountSignature(rawSignature, connected));
+}
+
 export default function FaucetPage() {
   const [form, setForm] = useState(defaultForm);
   const [submitting, setSubmitting] = useState(false);
@@ -71,13 +187,15 @@ export default function FaucetPage() {
       if (!owner) {
         throw new Error('Owner is required.');
       }
+      const signatureHex = await signFaucetMintWithMetaMask({ owner, amount });
       const mutationBody = {
-        query: `mutation FaucetMint($amount: String!, $owner: String!) {
-          faucetMint(amount: $amount, owner: $owner)
+        query: `mutation FaucetMint($amount: String!, $owner: String!, $sig: String!) {
+          faucetMint(request: { payload: { owner: $owner, amount: $amount }, signatureHex: $sig })
         }`,
         variables: {
           amount,
-          owner
+          owner,
+          sig: signatureHex
         }
       };
       const endpoint = form.endpoint.replace(/\/$/, '');
@@ -108,7 +226,7 @@ export default function FaucetPage() {
           <p className="text-xs uppercase tracking-[0.35em] text-slate-400">Linad.fun</p>
           <h1 className="mt-3 text-3xl font-semibold tracking-[0.18em] text-brand">wLIN FAUCET</h1>
           <p className="mt-3 text-sm text-slate-400">
-            Mint test wLin to your wallet. No signature required.
+            Mint test wLin to your wallet. MetaMask signs the claim.
           </p>
         </div>
 
@@ -143,7 +261,7 @@ export default function FaucetPage() {
               placeholder="Paste wallet address (0x...)"
               required
             />
-            <p className="text-[11px] text-slate-500">Enter a wallet address or connect MetaMask to auto-fill.</p>
+            <p className="text-[11px] text-slate-500">Connect MetaMask to auto-fill; the owner must match the connected wallet.</p>
           </div>
 
           <button
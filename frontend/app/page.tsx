@@ -12,7 +12,7 @@ const LS_WALLET = 'linad_wallet_address';
 const DEFAULT_CHAIN_ID = '761f62d709008c57a8eafb9d374522aa13f0a87b68ec4221861c73e0d1b67ced';
 const DEFAULT_TOKEN_FACTORY_APP_ID = 'ff081619d9553ae6919dd0ed2268cd1ad988140275701136fe54805d31027990';
 const DEFAULT_GRAPHQL_ENDPOINT = 'http://127.0.0.1:8080';
-const DEFAULT_DECIMALS = 9;
+const DEFAULT_DECIMALS = 18;
 const DEFAULT_SUPPLY = '800000000';
 
 function concatBytes(...chunks: Uint8Array[]) {
@@ -129,17 +129,29 @@ function encodeTokenMetadata(name: string, symbol: string, decimals: number) {
   return concatBytes(encodeString(name), encodeString(symbol), new Uint8Array([decimals]));
 }
 
+function encodeOptionalDevBuy(devBuy?: { amount: string; minOut: string }) {
+  if (!devBuy) {
+    // Option::None
+    return new Uint8Array([0]);
+  }
+  return concatBytes(new Uint8Array([1]), encodeAmount(devBuy.amount), encodeAmount(devBuy.minOut));
+}
+
 function encodeCreateTokenRequest(payload: {
   owner: string;
   name: string;
   symbol: string;
   decimals: number;
   supply: string;
+  devBuy?: { amount: string; minOut: string };
 }) {
   return concatBytes(
     encodeAccountOwner(payload.owner),
     encodeTokenMetadata(payload.name, payload.symbol, payload.decimals),
-    encodeAmount(payload.supply)
+    encodeAmount(payload.supply),
+    encodeOptionalDevBuy(payload.devBuy),
+    // `allocations`: the UI launches without locked allocations (empty Vec).
+    encodeUleb128(0)
   );
 }
 
@@ -0,0 +1,159 @@
+//! Request authentication shared by the intents-chain contracts.
+//!
+//! Every signed operation carries a payload with an `owner` and a
+//! `signature_hex`. [`authenticate`] accepts either an explicit signature over
+//! the payload or, when the signature is empty, an owner vouched for by the
+//! runtime (block signer, calling application or a trusted caller app).
+//! [`authenticate_typed`] additionally accepts EIP-712 wallet signatures, and
+//! [`ensure_admin`] gates admin-only operations.
+
+pub mod eip712;
+
+use linera_base::crypto::{AccountSignature, BcsSignable};
+use linera_base::data_types::Timestamp;
+use linera_base::identifiers::{AccountOwner, ApplicationId};
+use linera_sdk::{contract::ContractRuntime, Contract};
+use shared_types::{IntentsError, IntentsResult};
+
+use crate::eip712::Eip712;
+
+/// Authenticates `expected` as the owner behind `payload`.
+///
+/// With a non-empty `signature_hex` the signature must verify and belong to
+/// `expected`. With an empty one, `expected` is accepted if the operation comes
+/// from `trusted_caller`, from an application whose owner is `expected`, or
+/// from a block signed by `expected`.
+pub fn authenticate<C, T>(
+    runtime: &mut ContractRuntime<C>,
+    payload: &T,
+    expected: AccountOwner,
+    signature_hex: &str,
+    trusted_caller: Option<ApplicationId>,
+) -> IntentsResult<AccountOwner>
+where
+    C: Contract,
+    T: std::fmt::Debug,
+    for<'de> T: BcsSignable<'de>,
+{
+    authenticate_with(runtime, expected, signature_hex, trusted_caller, |_, bytes| {
+        verify_bcs(payload, bytes)
+    })
+}
+
+/// Like [`authenticate`], but a 65-byte `r || s || v` signature is checked as
+/// EIP-712 typed data for the current application instead of BCS.
+pub fn authenticate_typed<C, T>(
+    runtime: &mut ContractRuntime<C>,
+    payload: &T,
+    expected: AccountOwner,
+    signature_hex: &str,
+    trusted_caller: Option<ApplicationId>,
+) -> IntentsResult<AccountOwner>
+where
+    C: Contract,
+    T: std::fmt::Debug + Eip712,
+    for<'de> T: BcsSignable<'de>,
+{
+    authenticate_with(runtime, expected, signature_hex, trusted_caller, |runtime, bytes| {
+        if bytes.len() == eip712::SIGNATURE_LEN {
+            let application_id = runtime.application_id().forget_abi();
+            eip712::recover_signer(application_id, payload, bytes)
+        } else {
+            verify_bcs(payload, bytes)
+        }
+    })
+}
+
+/// Returns `admin` if the block is signed by it, `Unauthorized` otherwise
+/// (including when no admin is configured).
+pub fn ensure_admin<C: Contract>(
+    runtime: &mut ContractRuntime<C>,
+    admin: Option<AccountOwner>,
+) -> IntentsResult<AccountOwner> {
+    let admin = admin.ok_or(IntentsError::Unauthorized)?;
+    if runtime.authenticated_signer() != Some(admin) {
+        return Err(IntentsError::Unauthorized);
+    }
+    Ok(admin)
+}
+
+/// Replay guard for explicitly signed requests: fails once `deadline` has
+/// passed or when `nonce` is not the owner's `expected` nonce.
+pub fn ensure_fresh<C: Contract>(
+    runtime: &mut ContractRuntime<C>,
+    deadline: Timestamp,
+    nonce: u64,
+    expected: u64,
+) -> IntentsResult<()> {
+    check_fresh(runtime.system_time(), deadline, nonce, expected)
+}
+
+/// [`ensure_fresh`] evaluated at `now`.
+pub fn check_fresh(
+    now: Timestamp,
+    deadline: Timestamp,
+    nonce: u64,
+    expected: u64,
+) -> IntentsResult<()> {
+    if now > deadline {
+        return Err(IntentsError::RequestExpired);
+    }
+    if nonce != expected {
+        return Err(IntentsError::InvalidNonce);
+    }
+    Ok(())
+}
+
+fn authenticate_with<C, F>(
+    runtime: &mut ContractRuntime<C>,
+    expected: AccountOwner,
+    signature_hex: &str,
+    trusted_caller: Option<ApplicationId>,
+    verify: F,
+) -> IntentsResult<AccountOwner>
+where
+    C: Contract,
+    F: FnOnce(&mut ContractRuntime<C>, &[u8]) -> IntentsResult<AccountOwner>,
+{
+    if !signature_hex.is_empty() {
+        let bytes = decode_signature(signature_hex)?;
+        let signer = verify(runtime, &bytes)?;
+        if signer != expected {
+            return Err(IntentsError::SignerMismatch);
+        }
+        return Ok(signer);
+    }
+    if let Some(caller) = runtime.authenticated_caller_id() {
+        if trusted_caller == Some(caller) || AccountOwner::from(caller) == expected {
+            return Ok(expected);
+        }
+    }
+    match runtime.authenticated_signer() {
+        Some(signer) if signer == expected => Ok(signer),
+        Some(_) => Err(IntentsError::SignerMismatch),
+        None => Err(IntentsError::MissingSignature),
+    }
+}
+
+/// Hex-decodes a signature, tolerating the `0x` prefix wallets emit.
+fn decode_signature(signature_hex: &str) -> IntentsResult<Vec<u8>> {
+    let digits = signature_hex.strip_prefix("0x").unwrap_or(signature_hex);
+    hex::decode(digits).map_err(|_| IntentsError::InvalidSignatureHex)
+}
+
+fn verify_bcs<T>(payload: &T, bytes: &[u8]) -> IntentsResult<AccountOwner>
+where
+    T: std::fmt::Debug,
+    for<'de> T: BcsSignable<'de>,
+{
+    let signature =
+        AccountSignature::try_from(bytes).map_err(|_| IntentsError::InvalidSignature)?;
+    signature
+        .verify(payload)
+        .map_err(|_| IntentsError::SignatureVerificationFailed)?;
+    Ok(match signature {
+        AccountSignature::Ed25519 { public_key, .. } => AccountOwner::from(public_key),
+        AccountSignature::Secp256k1 { public_key, .. } => AccountOwner::from(public_key),
+        AccountSignature::EvmSecp256k1 { address, .. } => AccountOwner::Address20(address),
+    })
+}
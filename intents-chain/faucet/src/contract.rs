@@ -3,13 +3,19 @@
 //! Contract logic for the faucet app.
 
 use linera_base::abi::WithContractAbi;
-use linera_base::identifiers::ApplicationId;
-use linera_sdk::linera_base_types::Amount;
+use linera_base::identifiers::{AccountOwner, ApplicationId};
+use linera_sdk::linera_base_types::{Amount, Timestamp};
 use linera_sdk::{contract::ContractRuntime, Contract, views::{RootView, View}};
 
-use faucet::{FaucetAbi, Operation};
+use faucet::{
+    FaucetAbi, FaucetMode, Operation, SignedFaucetMintRequest, DEFAULT_REFILL_WINDOW_SECS,
+};
 use faucet::state::FaucetState;
-use fungible_token::{FungibleTokenAbi, Operation as FungibleOperation};
+use fungible_token::{
+    Account, FungibleTokenAbi, Operation as FungibleOperation, SignedTransferFromRequest,
+    SignedTransferRequest, TransferFromRequest, TransferRequest,
+};
+use shared_types::{IntentsError, IntentsResult};
 
 pub struct FaucetContract {
     state: FaucetState,
@@ -35,14 +41,16 @@ impl Contract for FaucetContract {
         FaucetContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _state: Self::InstantiationArgument) {}
+    async fn instantiate(&mut self, _state: Self::InstantiationArgument) {
+        let admin = self.runtime.authenticated_signer();
+        self.state.admin.set(admin);
+        self.state.refill_window_secs.set(DEFAULT_REFILL_WINDOW_SECS);
+    }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
-        match operation {
-            Operation::SetWlinApp { app_id } => self.set_wlin_app(app_id),
-            Operation::SetFaucetCap { amount } => self.state.faucet_cap.set(amount),
-            Operation::FaucetMint { amount, owner } => self.faucet_mint(amount, owner).await,
-        }
+        self.handle_operation(operation)
+            .await
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 
     async fn execute_message(&mut self, _message: Self::Message) {}
@@ -53,42 +61,140 @@ impl Contract for FaucetContract {
 }
 
 impl FaucetContract {
-    fn set_wlin_app(&mut self, app_id: String) {
-        let parsed = app_id.parse::<ApplicationId>().expect("Invalid app id");
+    async fn handle_operation(&mut self, operation: Operation) -> IntentsResult<()> {
+        match operation {
+            Operation::SetWlinApp { app_id } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.set_wlin_app(app_id)
+            }
+            Operation::SetFaucetCap { amount } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.faucet_cap.set(amount);
+                Ok(())
+            }
+            Operation::SetRefillWindow { secs } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.refill_window_secs.set(secs);
+                Ok(())
+            }
+            Operation::SetWindowBudget { amount } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.window_budget.set(amount);
+                Ok(())
+            }
+            Operation::SetFaucetMode { mode } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.mode.set(mode);
+                Ok(())
+            }
+            Operation::TopUp { amount } => self.top_up(amount),
+            Operation::FaucetMint { request } => self.faucet_mint(request).await,
+        }
+    }
+
+    fn set_wlin_app(&mut self, app_id: String) -> IntentsResult<()> {
+        let parsed = app_id
+            .parse::<ApplicationId>()
+            .map_err(|_| IntentsError::InvalidApplicationId)?;
         self.state.wlin_app_id.set(Some(parsed));
+        Ok(())
     }
 
-    async fn faucet_mint(&mut self, amount: Amount, owner: Option<String>) {
-        let owner = match owner {
-            Some(owner) => owner.parse().expect("Invalid owner"),
-            None => self.runtime.authenticated_signer().expect("Missing signer"),
-        };
-        let minted = self
+    async fn faucet_mint(&mut self, request: SignedFaucetMintRequest) -> IntentsResult<()> {
+        let owner = auth::authenticate(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
+        let amount = request.payload.amount;
+        let now = self.runtime.system_time();
+        let window_secs = *self.state.refill_window_secs.get();
+        let mut window = self
             .state
             .minted_by_owner
             .get(&owner)
             .await
             .expect("Failed to read minted")
-            .unwrap_or_default();
-        let cap = *self.state.faucet_cap.get();
-        if minted.saturating_add(amount) > cap {
-            panic!("Faucet cap exceeded");
+            .unwrap_or_default()
+            .at(now, window_secs);
+        window.minted = window.minted.saturating_add(amount);
+        if window.minted > *self.state.faucet_cap.get() {
+            return Err(IntentsError::FaucetCapExceeded);
+        }
+        let mut global = self.state.global_window.get().at(now, window_secs);
+        global.minted = global.minted.saturating_add(amount);
+        let budget = *self.state.window_budget.get();
+        if budget != Amount::ZERO && global.minted > budget {
+            return Err(IntentsError::FaucetBudgetExhausted);
         }
 
-        let wlin = self
-            .state
-            .wlin_app_id
-            .get()
-            .clone()
-            .expect("wLin app id not set")
-            .with_abi::<FungibleTokenAbi>();
-
-        let op = FungibleOperation::Mint { owner, amount };
+        let wlin = self.wlin_app()?;
+        let op = match *self.state.mode.get() {
+            FaucetMode::Mint => FungibleOperation::Mint { owner, amount },
+            // The wLin app rejects the transfer if the faucet balance is short.
+            FaucetMode::Treasury => FungibleOperation::Transfer {
+                request: SignedTransferRequest {
+                    payload: TransferRequest {
+                        owner: self.runtime.application_id().into(),
+                        amount,
+                        target_account: Account {
+                            chain_id: self.runtime.chain_id(),
+                            owner,
+                        },
+                        relayer_fee: Amount::ZERO,
+                        deadline: Timestamp::from(0),
+                        nonce: 0,
+                    },
+                    signature_hex: String::new(),
+                },
+            },
+        };
         self.runtime.call_application(true, wlin, &op);
 
         self.state
             .minted_by_owner
-            .insert(&owner, minted.saturating_add(amount))
+            .insert(&owner, window)
             .expect("Failed to update minted");
+        self.state.global_window.set(global);
+        let total = self.state.total_minted.get().saturating_add(amount);
+        self.state.total_minted.set(total);
+        Ok(())
+    }
+
+    /// Pulls `amount` wLin from the admin into the treasury via `TransferFrom`.
+    fn top_up(&mut self, amount: Amount) -> IntentsResult<()> {
+        let admin = auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+        if amount == Amount::ZERO {
+            return Err(IntentsError::ZeroAmount);
+        }
+        let wlin = self.wlin_app()?;
+        let faucet_owner: AccountOwner = self.runtime.application_id().into();
+        let op = FungibleOperation::TransferFrom {
+            request: SignedTransferFromRequest {
+                payload: TransferFromRequest {
+                    owner: admin,
+                    spender: faucet_owner,
+                    amount,
+                    target_account: Account {
+                        chain_id: self.runtime.chain_id(),
+                        owner: faucet_owner,
+                    },
+                },
+                signature_hex: String::new(),
+            },
+        };
+        self.runtime.call_application(true, wlin, &op);
+        Ok(())
+    }
+
+    fn wlin_app(&self) -> IntentsResult<ApplicationId<FungibleTokenAbi>> {
+        self.state
+            .wlin_app_id
+            .get()
+            .as_ref()
+            .map(|id| id.with_abi::<FungibleTokenAbi>())
+            .ok_or(IntentsError::WlinAppNotSet)
     }
 }
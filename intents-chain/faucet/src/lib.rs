@@ -1,27 +1,100 @@
 //! Faucet app crate root.
 //!
-//! Provides capped minting of wLin via transfers from a pre-funded faucet account.
+//! Provides rate-limited minting of wLin: each owner may mint up to the faucet
+//! cap per refill window, within a global per-window budget. In treasury mode
+//! claims are paid from the faucet's own pre-funded wLin balance instead.
 
 pub mod state;
 
-use linera_sdk::{graphql::GraphQLMutationRoot, linera_base_types::{ContractAbi, ServiceAbi}};
+use linera_base::crypto::BcsSignable;
+use linera_sdk::{
+    graphql::GraphQLMutationRoot,
+    linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi, TimeDelta, Timestamp},
+};
 use serde::{Deserialize, Serialize};
 
+/// Refill window used until the operator configures one (24h).
+pub const DEFAULT_REFILL_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Where faucet claims are paid from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, async_graphql::Enum)]
+pub enum FaucetMode {
+    /// Mint new wLin (unbounded supply).
+    #[default]
+    Mint,
+    /// Transfer from the faucet's topped-up wLin balance.
+    Treasury,
+}
+
 /// ABI definition for the faucet app.
 pub struct FaucetAbi;
 
 /// Operations executed by the faucet app.
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
-    /// Operator sets the wLin app id.
+    /// Admin sets the wLin app id.
     SetWlinApp { app_id: String },
-    /// Operator sets the per-user faucet cap.
-    SetFaucetCap { amount: linera_sdk::linera_base_types::Amount },
-    /// User requests faucet mint of wLin. Optional owner overrides the signer.
-    FaucetMint {
-        amount: linera_sdk::linera_base_types::Amount,
-        owner: Option<String>,
-    },
+    /// Admin sets the per-user cap for each refill window.
+    SetFaucetCap { amount: Amount },
+    /// Admin sets the refill window length.
+    SetRefillWindow { secs: u64 },
+    /// Admin sets the global budget per refill window (zero = unlimited).
+    SetWindowBudget { amount: Amount },
+    /// Admin switches between minting and treasury payouts.
+    SetFaucetMode { mode: FaucetMode },
+    /// Admin moves wLin from their balance into the faucet treasury
+    /// (requires an allowance for the faucet app).
+    TopUp { amount: Amount },
+    /// User requests faucet mint of wLin to themselves.
+    FaucetMint { request: SignedFaucetMintRequest },
+}
+
+/// Faucet claim (signed by the beneficiary).
+///
+/// Used by `Operation::FaucetMint` after signature verification; an empty
+/// signature is accepted when `owner` signed the block.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct FaucetMintRequest {
+    /// Beneficiary / signer.
+    pub owner: AccountOwner,
+    /// Amount of wLin to mint.
+    pub amount: Amount,
+}
+
+impl<'de> BcsSignable<'de> for FaucetMintRequest {}
+
+/// Signed faucet claim.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct SignedFaucetMintRequest {
+    /// Claim payload.
+    pub payload: FaucetMintRequest,
+    /// Hex-encoded AccountSignature bytes.
+    pub signature_hex: String,
+}
+
+/// Amount minted within a refill window that opened at `started_at`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct FaucetWindow {
+    /// When the window opened.
+    pub started_at: Timestamp,
+    /// Amount minted since then.
+    pub minted: Amount,
+}
+
+impl FaucetWindow {
+    /// When this window closes.
+    pub fn ends_at(&self, window_secs: u64) -> Timestamp {
+        self.started_at.saturating_add(TimeDelta::from_secs(window_secs))
+    }
+
+    /// The window in force at `now`: a fresh one once this one has closed.
+    pub fn at(self, now: Timestamp, window_secs: u64) -> Self {
+        if now >= self.ends_at(window_secs) {
+            FaucetWindow { started_at: now, minted: Amount::ZERO }
+        } else {
+            self
+        }
+    }
 }
 
 impl ContractAbi for FaucetAbi {
@@ -33,3 +106,42 @@ impl ServiceAbi for FaucetAbi {
     type Query = async_graphql::Request;
     type QueryResponse = async_graphql::Response;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000;
+
+    fn window() -> FaucetWindow {
+        FaucetWindow {
+            started_at: Timestamp::from(100 * SECOND),
+            minted: Amount::from_tokens(5),
+        }
+    }
+
+    #[test]
+    fn window_is_kept_until_it_closes() {
+        assert_eq!(window().ends_at(60), Timestamp::from(160 * SECOND));
+        let kept = window().at(Timestamp::from(159 * SECOND), 60);
+        assert_eq!(kept.started_at, Timestamp::from(100 * SECOND));
+        assert_eq!(kept.minted, Amount::from_tokens(5));
+    }
+
+    #[test]
+    fn closed_window_restarts_at_now() {
+        for now in [160 * SECOND, 1_000 * SECOND] {
+            let fresh = window().at(Timestamp::from(now), 60);
+            assert_eq!(fresh.started_at, Timestamp::from(now));
+            assert_eq!(fresh.minted, Amount::ZERO);
+        }
+    }
+
+    #[test]
+    fn default_window_restarts_on_first_use() {
+        let now = Timestamp::from(1_700_000_000 * SECOND);
+        let fresh = FaucetWindow::default().at(now, DEFAULT_REFILL_WINDOW_SECS);
+        assert_eq!(fresh.started_at, now);
+        assert_eq!(fresh.minted, Amount::ZERO);
+    }
+}
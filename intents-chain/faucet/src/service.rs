@@ -4,7 +4,7 @@
 
 use std::sync::Arc;
 
-use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
+use async_graphql::{EmptySubscription, Object, Request, Response, Schema, Value};
 use linera_sdk::{
     graphql::GraphQLMutationRoot as _,
     linera_base_types::WithServiceAbi,
@@ -12,8 +12,9 @@ use linera_sdk::{
     Service, ServiceRuntime,
 };
 
-use faucet::{FaucetAbi, Operation};
+use faucet::{FaucetAbi, FaucetMode, FaucetWindow, Operation};
 use faucet::state::FaucetState;
+use fungible_token::FungibleTokenAbi;
 
 #[derive(Clone)]
 pub struct FaucetService {
@@ -61,11 +62,101 @@ impl FaucetService {
         *self.state.faucet_cap.get()
     }
 
-    async fn minted_amount(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> Option<linera_sdk::linera_base_types::Amount> {
+    async fn admin(&self) -> Option<linera_sdk::linera_base_types::AccountOwner> {
+        *self.state.admin.get()
+    }
+
+    async fn faucet_mode(&self) -> FaucetMode {
+        *self.state.mode.get()
+    }
+
+    /// wLin held by the faucet app, read from the wLin app, for treasury-mode payouts.
+    async fn treasury(&self) -> linera_sdk::linera_base_types::Amount {
+        self.treasury_balance()
+    }
+
+    async fn refill_window_secs(&self) -> u64 {
+        *self.state.refill_window_secs.get()
+    }
+
+    async fn window_budget(&self) -> linera_sdk::linera_base_types::Amount {
+        *self.state.window_budget.get()
+    }
+
+    /// Amount the owner minted in their current refill window.
+    async fn minted_by(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> linera_sdk::linera_base_types::Amount {
+        self.owner_window(owner).await.minted
+    }
+
+    /// Former name of `mintedBy`, kept for existing clients.
+    #[graphql(deprecation = "Use `mintedBy`")]
+    async fn minted_amount(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> linera_sdk::linera_base_types::Amount {
+        self.minted_by(owner).await
+    }
+
+    /// Amount the owner may still mint in their current refill window,
+    /// bounded by what is left of the global budget and treasury.
+    async fn remaining(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> linera_sdk::linera_base_types::Amount {
+        let minted = self.owner_window(owner).await.minted;
+        let mut remaining = self.state.faucet_cap.get().saturating_sub(minted);
+        if *self.state.mode.get() == FaucetMode::Treasury {
+            remaining = remaining.min(self.treasury_balance());
+        }
+        let budget = *self.state.window_budget.get();
+        if budget == linera_sdk::linera_base_types::Amount::ZERO {
+            return remaining;
+        }
+        let global = self
+            .state
+            .global_window
+            .get()
+            .at(self.runtime.system_time(), *self.state.refill_window_secs.get());
+        remaining.min(budget.saturating_sub(global.minted))
+    }
+
+    /// Total minted through the faucet since instantiation.
+    async fn total_minted(&self) -> linera_sdk::linera_base_types::Amount {
+        *self.state.total_minted.get()
+    }
+
+    /// Earliest time the owner can mint again: now unless their cap is used up.
+    async fn next_eligible_at(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> linera_sdk::linera_base_types::Timestamp {
+        let window = self.owner_window(owner).await;
+        if window.minted < *self.state.faucet_cap.get() {
+            return self.runtime.system_time();
+        }
+        window.ends_at(*self.state.refill_window_secs.get())
+    }
+}
+
+impl FaucetService {
+    /// The owner's usage in the window in force now.
+    async fn owner_window(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> FaucetWindow {
         self.state
             .minted_by_owner
             .get(&owner)
             .await
             .expect("Failed to read minted")
+            .unwrap_or_default()
+            .at(self.runtime.system_time(), *self.state.refill_window_secs.get())
+    }
+
+    /// The faucet app's own wLin balance (zero when wLin is not configured).
+    fn treasury_balance(&self) -> linera_sdk::linera_base_types::Amount {
+        let Some(wlin) = *self.state.wlin_app_id.get() else {
+            return linera_sdk::linera_base_types::Amount::ZERO;
+        };
+        let owner = linera_sdk::linera_base_types::AccountOwner::from(self.runtime.application_id());
+        let request = Request::new(format!("query {{ balance(owner: \"{owner}\") }}"));
+        let response = self
+            .runtime
+            .query_application(wlin.with_abi::<FungibleTokenAbi>(), &request);
+        match response.data {
+            Value::Object(data) => match data.get("balance") {
+                Some(Value::String(balance)) => balance.parse().expect("Invalid wLin balance"),
+                _ => linera_sdk::linera_base_types::Amount::ZERO,
+            },
+            _ => linera_sdk::linera_base_types::Amount::ZERO,
+        }
     }
 }
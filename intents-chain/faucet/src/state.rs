@@ -4,13 +4,27 @@ use linera_base::identifiers::ApplicationId;
 use linera_sdk::linera_base_types::{AccountOwner, Amount};
 use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
 
+use crate::{FaucetMode, FaucetWindow};
+
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct FaucetState {
+    /// Admin allowed to change the mode and top up the treasury.
+    pub admin: RegisterView<Option<AccountOwner>>,
+    /// Where claims are paid from.
+    pub mode: RegisterView<FaucetMode>,
     /// wLin application id (pricing asset).
     pub wlin_app_id: RegisterView<Option<ApplicationId>>,
-    /// Per-user faucet cap.
+    /// Per-user cap for each refill window.
     pub faucet_cap: RegisterView<Amount>,
-    /// Amount minted per user.
-    pub minted_by_owner: MapView<AccountOwner, Amount>,
+    /// Refill window length in seconds.
+    pub refill_window_secs: RegisterView<u64>,
+    /// Global budget per refill window; zero means unlimited.
+    pub window_budget: RegisterView<Amount>,
+    /// Global usage in the current refill window.
+    pub global_window: RegisterView<FaucetWindow>,
+    /// Total minted through the faucet since instantiation.
+    pub total_minted: RegisterView<Amount>,
+    /// Per-user usage in their current refill window.
+    pub minted_by_owner: MapView<AccountOwner, FaucetWindow>,
 }
@@ -3,16 +3,17 @@
 //! Contract logic for the signed fungible token app.
 
 use linera_base::abi::WithContractAbi;
-use linera_base::crypto::AccountSignature;
 use linera_base::identifiers::AccountOwner;
-use linera_sdk::linera_base_types::Amount;
+use linera_sdk::linera_base_types::{Account as NativeAccount, Amount, Timestamp};
 use linera_sdk::{contract::ContractRuntime, Contract, views::{RootView, View}};
 
 use fungible_token::{
-    FungibleTokenAbi, InitialState, Operation, Parameters, SignedApproveRequest,
-    SignedClaimRequest, SignedTransferFromRequest, SignedTransferRequest,
+    Account, FungibleTokenAbi, InitialState, Message, Operation, Parameters, SignedApproveRequest,
+    SignedBatchTransferRequest, SignedClaimRequest, SignedClaimVestedRequest, SignedPermitRequest,
+    SignedTransferFromRequest, SignedTransferRequest, SignedUnwrapRequest, VestingSchedule,
 };
 use fungible_token::state::FungibleState;
+use shared_types::{IntentsError, IntentsResult};
 
 pub struct FungibleContract {
     state: FungibleState,
@@ -26,7 +27,7 @@ impl WithContractAbi for FungibleContract {
 }
 
 impl Contract for FungibleContract {
-    type Message = ();
+    type Message = Message;
     type Parameters = Parameters;
     type InstantiationArgument = InitialState;
     type EventValue = ();
@@ -52,45 +53,165 @@ impl Contract for FungibleContract {
                 .insert(&owner, current.saturating_add(amount))
                 .expect("Failed to update balance");
         }
+        let start = self.runtime.system_time();
+        for allocation in state.vesting {
+            let schedule = VestingSchedule {
+                total: allocation.amount,
+                claimed: Amount::ZERO,
+                start,
+                cliff_secs: allocation.cliff_secs,
+                vesting_secs: allocation.vesting_secs,
+            };
+            self.state
+                .vesting
+                .insert(&allocation.beneficiary, schedule)
+                .expect("Failed to insert vesting schedule");
+        }
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        self.handle_operation(operation)
+            .await
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    async fn execute_message(&mut self, message: Self::Message) {
+        self.handle_message(message)
+            .await
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl FungibleContract {
+    async fn handle_operation(&mut self, operation: Operation) -> IntentsResult<()> {
         match operation {
             Operation::Transfer { request } => self.transfer(request).await,
             Operation::TransferFrom { request } => self.transfer_from(request).await,
+            Operation::BatchTransfer { request } => self.batch_transfer(request).await,
             Operation::Approve { request } => self.approve(request).await,
+            Operation::Permit { request } => self.permit(request).await,
             Operation::Claim { request } => self.claim(request).await,
             Operation::Mint { owner, amount } => self.mint(owner, amount).await,
+            Operation::ClaimVested { request } => self.claim_vested(request).await,
+            Operation::Wrap { amount, beneficiary } => self.wrap(amount, beneficiary).await,
+            Operation::Unwrap { request } => self.unwrap(request).await,
         }
     }
 
-    async fn execute_message(&mut self, _message: Self::Message) {}
-
-    async fn store(mut self) {
-        self.state.save().await.expect("Failed to save state");
+    async fn handle_message(&mut self, message: Message) -> IntentsResult<()> {
+        match message {
+            Message::Credit {
+                target,
+                amount,
+                source,
+            } => {
+                // A bounced credit returns to the sender on the origin chain.
+                let is_bouncing = self.runtime.message_is_bouncing() == Some(true);
+                let owner = if is_bouncing { source } else { target };
+                self.credit(owner, amount).await;
+                Ok(())
+            }
+            Message::Withdraw { request } => {
+                // Rejected withdrawals bounce back with nothing to refund.
+                if self.runtime.message_is_bouncing() == Some(true) {
+                    return Ok(());
+                }
+                self.claim(request).await
+            }
+        }
     }
-}
 
-impl FungibleContract {
-    async fn transfer(&mut self, request: SignedTransferRequest) {
-        let owner = self.resolve_signer(&request.payload, request.payload.owner, &request.signature_hex);
-        if request.payload.owner != owner {
-            panic!("Signature owner mismatch");
-        }
-        self.debit_credit(
+    async fn transfer(&mut self, request: SignedTransferRequest) -> IntentsResult<()> {
+        let owner = request.payload.owner;
+        self.authenticate(&request.payload, owner, &request.signature_hex)?;
+        self.consume_transfer_nonce(
+            owner,
+            &request.signature_hex,
+            request.payload.deadline,
+            request.payload.nonce,
+        )
+        .await?;
+        self.pay_relayer_fee(request.payload.owner, request.payload.relayer_fee)
+            .await?;
+        self.send_to_account(
             request.payload.owner,
-            request.payload.target_account.owner,
+            request.payload.target_account,
             request.payload.amount,
         )
-        .await;
+        .await
     }
 
-    async fn transfer_from(&mut self, request: SignedTransferFromRequest) {
-        // Require payload owner signature.
-        let owner =
-            self.resolve_signer(&request.payload, request.payload.owner, &request.signature_hex);
-        if request.payload.owner != owner {
-            panic!("Signature owner mismatch");
+    async fn batch_transfer(&mut self, request: SignedBatchTransferRequest) -> IntentsResult<()> {
+        let owner = request.payload.owner;
+        self.authenticate(&request.payload, owner, &request.signature_hex)?;
+        let total = request.payload.total()?;
+        self.consume_transfer_nonce(
+            owner,
+            &request.signature_hex,
+            request.payload.deadline,
+            request.payload.nonce,
+        )
+        .await?;
+        self.debit(owner, total).await?;
+        for recipient in request.payload.recipients {
+            self.credit(recipient.owner, recipient.amount).await;
+        }
+        Ok(())
+    }
+
+    /// Consumes `owner`'s transfer nonce for a request carrying its own signature.
+    ///
+    /// Only a standalone signature can be resubmitted by a relayer, so
+    /// block-signed requests skip the check.
+    async fn consume_transfer_nonce(
+        &mut self,
+        owner: AccountOwner,
+        signature_hex: &str,
+        deadline: Timestamp,
+        nonce: u64,
+    ) -> IntentsResult<()> {
+        if signature_hex.is_empty() {
+            return Ok(());
+        }
+        let expected = self
+            .state
+            .transfer_nonces
+            .get(&owner)
+            .await
+            .expect("Failed to read transfer nonce")
+            .unwrap_or_default();
+        auth::ensure_fresh(&mut self.runtime, deadline, nonce, expected)?;
+        self.state
+            .transfer_nonces
+            .insert(&owner, expected + 1)
+            .expect("Failed to update transfer nonce");
+        Ok(())
+    }
+
+    /// Pays `fee` of this token from `owner` to the block signer relaying the
+    /// request (see the crate docs for why this is not wLin).
+    async fn pay_relayer_fee(&mut self, owner: AccountOwner, fee: Amount) -> IntentsResult<()> {
+        if fee == Amount::ZERO {
+            return Ok(());
+        }
+        match self.runtime.authenticated_signer() {
+            Some(relayer) if relayer != owner => self.debit_credit(owner, relayer, fee).await,
+            _ => Ok(()),
+        }
+    }
+
+    async fn transfer_from(&mut self, request: SignedTransferFromRequest) -> IntentsResult<()> {
+        // An app pulling with its own allowance is authorized by that allowance;
+        // otherwise require payload owner signature.
+        let caller_is_spender = request.signature_hex.is_empty()
+            && self.runtime.authenticated_caller_id().map(AccountOwner::from)
+                == Some(request.payload.spender);
+        if !caller_is_spender {
+            self.authenticate(&request.payload, request.payload.owner, &request.signature_hex)?;
         }
         let key = (request.payload.owner, request.payload.spender);
         let current = self
@@ -101,53 +222,98 @@ impl FungibleContract {
             .expect("Failed to read allowance")
             .unwrap_or_default();
         if current < request.payload.amount {
-            panic!("Allowance exceeded");
+            return Err(IntentsError::AllowanceExceeded);
         }
         self.state
             .allowances
             .insert(&key, current.saturating_sub(request.payload.amount))
             .expect("Failed to update allowance");
-        self.debit_credit(
+        self.send_to_account(
             request.payload.owner,
-            request.payload.target_account.owner,
+            request.payload.target_account,
             request.payload.amount,
         )
-        .await;
+        .await
     }
 
-    async fn approve(&mut self, request: SignedApproveRequest) {
-        let owner = self.resolve_signer(&request.payload, request.payload.owner, &request.signature_hex);
-        if request.payload.owner != owner {
-            panic!("Signature owner mismatch");
-        }
+    async fn approve(&mut self, request: SignedApproveRequest) -> IntentsResult<()> {
+        self.authenticate(&request.payload, request.payload.owner, &request.signature_hex)?;
         let key = (request.payload.owner, request.payload.spender);
         self.state
             .allowances
             .insert(&key, request.payload.allowance)
             .expect("Failed to update allowance");
+        Ok(())
     }
 
-    async fn claim(&mut self, request: SignedClaimRequest) {
-        let owner = self.resolve_signer(&request.payload, request.payload.source_account.owner, &request.signature_hex);
-        if request.payload.source_account.owner != owner {
-            panic!("Signature owner mismatch");
+    async fn permit(&mut self, request: SignedPermitRequest) -> IntentsResult<()> {
+        self.authenticate(&request.payload, request.payload.owner, &request.signature_hex)?;
+        if self.runtime.system_time() > request.payload.deadline {
+            return Err(IntentsError::PermitExpired);
+        }
+        let owner = request.payload.owner;
+        let nonce = self
+            .state
+            .permit_nonces
+            .get(&owner)
+            .await
+            .expect("Failed to read permit nonce")
+            .unwrap_or_default();
+        if request.payload.nonce != nonce {
+            return Err(IntentsError::InvalidPermitNonce);
         }
-        if request.payload.source_account.chain_id != self.runtime.chain_id() ||
-           request.payload.target_account.chain_id != self.runtime.chain_id() {
-            panic!("Claim is only supported on the same chain in this design");
+        self.state
+            .permit_nonces
+            .insert(&owner, nonce + 1)
+            .expect("Failed to update permit nonce");
+        self.state
+            .allowances
+            .insert(&(owner, request.payload.spender), request.payload.allowance)
+            .expect("Failed to update allowance");
+        Ok(())
+    }
+
+    async fn claim(&mut self, request: SignedClaimRequest) -> IntentsResult<()> {
+        self.authenticate(&request.payload, request.payload.source_account.owner, &request.signature_hex)?;
+        let source_chain = request.payload.source_account.chain_id;
+        if source_chain != self.runtime.chain_id() {
+            self.ensure_local_backing()?;
+            // The source balance lives on another chain: claim there.
+            self.runtime
+                .prepare_message(Message::Withdraw { request })
+                .with_authentication()
+                .with_tracking()
+                .send_to(source_chain);
+            return Ok(());
         }
-        self.debit_credit(
+        // Checked where the balance is debited, so a relayed `Withdraw`
+        // consumes the nonce exactly once.
+        self.consume_transfer_nonce(
             request.payload.source_account.owner,
-            request.payload.target_account.owner,
+            &request.signature_hex,
+            request.payload.deadline,
+            request.payload.nonce,
+        )
+        .await?;
+        self.send_to_account(
+            request.payload.source_account.owner,
+            request.payload.target_account,
             request.payload.amount,
         )
-        .await;
+        .await
     }
 
-    async fn mint(&mut self, owner: AccountOwner, amount: Amount) {
-        // NOTE: Mint is now permissionless at the token contract level.
-        // The faucet still enforces the per-owner cap for faucetMint, but
-        // direct Mint calls will no longer be blocked here.
+    async fn mint(&mut self, owner: AccountOwner, amount: Amount) -> IntentsResult<()> {
+        let params = self.runtime.application_parameters();
+        // Wrapped supply must stay backed by native deposits.
+        if params.wrapped_native {
+            return Err(IntentsError::MintDisabled);
+        }
+        // Only the minter app (the faucet, which enforces its caps) may mint.
+        let minter = params.minter_app_id.ok_or(IntentsError::MintDisabled)?;
+        if self.runtime.authenticated_caller_id() != Some(minter) {
+            return Err(IntentsError::Unauthorized);
+        }
         let current = self
             .state
             .balances
@@ -159,9 +325,129 @@ impl FungibleContract {
             .balances
             .insert(&owner, current.saturating_add(amount))
             .expect("Failed to update balance");
+        Ok(())
     }
 
-    async fn debit_credit(&mut self, from: AccountOwner, to: AccountOwner, amount: Amount) {
+    async fn claim_vested(&mut self, request: SignedClaimVestedRequest) -> IntentsResult<()> {
+        let owner = request.payload.owner;
+        self.authenticate(&request.payload, owner, &request.signature_hex)?;
+        let mut schedule = self
+            .state
+            .vesting
+            .get(&owner)
+            .await
+            .expect("Failed to read vesting schedule")
+            .ok_or(IntentsError::NothingVested)?;
+        let claimable = schedule.claimable_at(self.runtime.system_time());
+        if claimable == Amount::ZERO {
+            return Err(IntentsError::NothingVested);
+        }
+        schedule.claimed = schedule.claimed.saturating_add(claimable);
+        self.state
+            .vesting
+            .insert(&owner, schedule)
+            .expect("Failed to update vesting schedule");
+        self.credit(owner, claimable).await;
+        Ok(())
+    }
+
+    async fn wrap(&mut self, amount: Amount, beneficiary: Option<AccountOwner>) -> IntentsResult<()> {
+        self.ensure_wrapped_native()?;
+        if amount == Amount::ZERO {
+            return Err(IntentsError::ZeroAmount);
+        }
+        // Only the block signer's native balance can be debited by this app.
+        let depositor = self
+            .runtime
+            .authenticated_signer()
+            .ok_or(IntentsError::MissingSignature)?;
+        let custody = NativeAccount {
+            chain_id: self.runtime.chain_id(),
+            owner: self.runtime.application_id().into(),
+        };
+        self.runtime.transfer(depositor, custody, amount);
+        self.credit(beneficiary.unwrap_or(depositor), amount).await;
+        Ok(())
+    }
+
+    async fn unwrap(&mut self, request: SignedUnwrapRequest) -> IntentsResult<()> {
+        self.ensure_wrapped_native()?;
+        self.authenticate(&request.payload, request.payload.owner, &request.signature_hex)?;
+        let owner = request.payload.owner;
+        let amount = request.payload.amount;
+        if amount == Amount::ZERO {
+            return Err(IntentsError::ZeroAmount);
+        }
+        self.consume_transfer_nonce(
+            owner,
+            &request.signature_hex,
+            request.payload.deadline,
+            request.payload.nonce,
+        )
+        .await?;
+        self.debit(owner, amount).await?;
+        let destination = NativeAccount {
+            chain_id: self.runtime.chain_id(),
+            owner,
+        };
+        let custody = self.runtime.application_id().into();
+        self.runtime.transfer(custody, destination, amount);
+        Ok(())
+    }
+
+    fn ensure_wrapped_native(&mut self) -> IntentsResult<()> {
+        if !self.runtime.application_parameters().wrapped_native {
+            return Err(IntentsError::NotWrappedNative);
+        }
+        Ok(())
+    }
+
+    /// Wrapped balances are backed by native tokens held on this chain, so
+    /// they must not be credited or claimed on another one.
+    fn ensure_local_backing(&mut self) -> IntentsResult<()> {
+        if self.runtime.application_parameters().wrapped_native {
+            return Err(IntentsError::WrappedNativeCrossChain);
+        }
+        Ok(())
+    }
+
+    /// Debits `from` and credits `target`, locally or via a tracked
+    /// `Message::Credit` when the account lives on another chain.
+    async fn send_to_account(
+        &mut self,
+        from: AccountOwner,
+        target: Account,
+        amount: Amount,
+    ) -> IntentsResult<()> {
+        if target.chain_id == self.runtime.chain_id() {
+            return self.debit_credit(from, target.owner, amount).await;
+        }
+        self.ensure_local_backing()?;
+        self.debit(from, amount).await?;
+        self.runtime
+            .prepare_message(Message::Credit {
+                target: target.owner,
+                amount,
+                source: from,
+            })
+            .with_authentication()
+            .with_tracking()
+            .send_to(target.chain_id);
+        Ok(())
+    }
+
+    async fn debit_credit(
+        &mut self,
+        from: AccountOwner,
+        to: AccountOwner,
+        amount: Amount,
+    ) -> IntentsResult<()> {
+        self.debit(from, amount).await?;
+        self.credit(to, amount).await;
+        Ok(())
+    }
+
+    async fn debit(&mut self, from: AccountOwner, amount: Amount) -> IntentsResult<()> {
         let from_balance = self
             .state
             .balances
@@ -170,12 +456,16 @@ impl FungibleContract {
             .expect("Failed to read balance")
             .unwrap_or_default();
         if from_balance < amount {
-            panic!("Insufficient balance");
+            return Err(IntentsError::InsufficientBalance);
         }
         self.state
             .balances
             .insert(&from, from_balance.saturating_sub(amount))
             .expect("Failed to update balance");
+        Ok(())
+    }
+
+    async fn credit(&mut self, to: AccountOwner, amount: Amount) {
         let to_balance = self
             .state
             .balances
@@ -189,45 +479,19 @@ impl FungibleContract {
             .expect("Failed to update balance");
     }
 
-    fn resolve_signer<T: std::fmt::Debug>(
-        &mut self,  
+    /// Authenticates `expected` (BCS or EIP-712 signature), trusting the
+    /// configured caller app (the matching engine).
+    fn authenticate<T>(
+        &mut self,
         payload: &T,
-        _expected: AccountOwner,
+        expected: AccountOwner,
         signature_hex: &str,
-    ) -> AccountOwner
+    ) -> IntentsResult<AccountOwner>
     where
+        T: std::fmt::Debug + auth::eip712::Eip712,
         for<'de> T: linera_base::crypto::BcsSignable<'de>,
     {
-        if signature_hex.is_empty() {
-            // Allow empty signature if caller is trusted matching_engine, or signer matches expected.
-            if let Some(caller) = self.runtime.authenticated_caller_id() {
-                let params = self.runtime.application_parameters();
-                if params.trusted_caller_app_id == Some(caller) {
-                    return _expected;
-                }
-            }
-            if let Some(signer) = self.runtime.authenticated_signer() {
-                if _expected == signer {
-                    return signer;
-                }
-            }
-            panic!("Missing signature");
-        }
-        self.verify_signature(payload, signature_hex)
-    }
-
-    fn verify_signature<T: std::fmt::Debug>(&self, payload: &T, signature_hex: &str) -> AccountOwner
-    where
-        for<'de> T: linera_base::crypto::BcsSignable<'de>,
-    {
-        let bytes = hex::decode(signature_hex).expect("Invalid signature hex");
-        let signature = AccountSignature::try_from(bytes.as_slice())
-            .expect("Invalid account signature");
-        signature.verify(payload).expect("Signature verification failed");
-        match signature {
-            AccountSignature::Ed25519 { public_key, .. } => AccountOwner::from(public_key),
-            AccountSignature::Secp256k1 { public_key, .. } => AccountOwner::from(public_key),
-            AccountSignature::EvmSecp256k1 { address, .. } => AccountOwner::Address20(address),
-        }
+        let trusted_caller = self.runtime.application_parameters().trusted_caller_app_id;
+        auth::authenticate_typed(&mut self.runtime, payload, expected, signature_hex, trusted_caller)
     }
 }
@@ -1,18 +1,30 @@
 //! Signed fungible token app.
 //!
-//! Implements transfer/approve/transferFrom/allowance/claim using signed payloads.
+//! Implements transfer/batchTransfer/approve/transferFrom/allowance/claim using signed payloads.
+//! Transfers to accounts on other chains are delivered as tracked messages and
+//! refunded to the sender if the receiving chain rejects them.
+//! A signed transfer may tip the block signer relaying it. Unlike matching-engine
+//! requests, which tip in wLin, the tip is paid in the token being transferred:
+//! a token app holds no wLin allowance it could charge.
 //! Mint is restricted to an optional minter app id (used by the faucet for wLin).
+//! In wrapper mode the token is backed 1:1 by native tokens held by the app:
+//! `Wrap` mints against a native deposit and `Unwrap` burns and releases them.
+//! The backing stays on the chain where it was deposited, so wrapped balances
+//! never move across chains.
 
 pub mod state;
 
 use async_graphql::scalar;
+use auth::eip712::{encode_amount, encode_array, encode_owner, encode_str, encode_uint, Eip712};
 use linera_base::crypto::BcsSignable;
 use linera_base::identifiers::ApplicationId;
 use linera_sdk::{
     graphql::GraphQLMutationRoot,
-    linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi},
+    linera_base_types::{AccountOwner, Amount, ContractAbi, ServiceAbi, Timestamp},
 };
 use serde::{Deserialize, Serialize};
+use shared_types::{Allocation, IntentsError, IntentsResult};
+pub use shared_types::MAX_BATCH_RECIPIENTS;
 
 /// Parameters for a fungible token app instance.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -20,6 +32,9 @@ pub struct Parameters {
     pub ticker_symbol: String,
     pub minter_app_id: Option<ApplicationId>,
     pub trusted_caller_app_id: Option<ApplicationId>,
+    /// Backs the token 1:1 with native tokens (wLin); disables `Mint`.
+    #[serde(default)]
+    pub wrapped_native: bool,
 }
 
 impl Parameters {
@@ -28,7 +43,13 @@ impl Parameters {
         minter_app_id: Option<ApplicationId>,
         trusted_caller_app_id: Option<ApplicationId>,
     ) -> Self {
-        Self { ticker_symbol, minter_app_id, trusted_caller_app_id }
+        Self { ticker_symbol, minter_app_id, trusted_caller_app_id, wrapped_native: false }
+    }
+
+    /// Switches the instance to native-wrapping mode.
+    pub fn with_wrapped_native(mut self) -> Self {
+        self.wrapped_native = true;
+        self
     }
 }
 
@@ -36,11 +57,15 @@ impl Parameters {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InitialState {
     pub balances: Vec<(AccountOwner, Amount)>,
+    /// Locked allocations, vesting from instantiation time.
+    #[serde(default)]
+    pub vesting: Vec<Allocation>,
 }
 
 #[derive(Default)]
 pub struct InitialStateBuilder {
     balances: Vec<(AccountOwner, Amount)>,
+    vesting: Vec<Allocation>,
 }
 
 impl InitialStateBuilder {
@@ -49,12 +74,53 @@ impl InitialStateBuilder {
         self
     }
 
+    pub fn with_vesting(mut self, allocation: Allocation) -> Self {
+        self.vesting.push(allocation);
+        self
+    }
+
     pub fn build(self) -> InitialState {
-        InitialState { balances: self.balances }
+        InitialState { balances: self.balances, vesting: self.vesting }
+    }
+}
+
+/// Locked allocation held by the token app until claimed by the beneficiary.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, async_graphql::SimpleObject)]
+pub struct VestingSchedule {
+    /// Total tokens locked.
+    pub total: Amount,
+    /// Tokens already released to the beneficiary.
+    pub claimed: Amount,
+    /// When vesting started (token instantiation).
+    pub start: Timestamp,
+    /// Seconds after `start` before anything vests.
+    pub cliff_secs: u64,
+    /// Seconds after `start` until everything has vested.
+    pub vesting_secs: u64,
+}
+
+impl VestingSchedule {
+    /// Tokens vested at `now`, claimed or not.
+    pub fn vested_at(&self, now: Timestamp) -> Amount {
+        let elapsed_secs = now.delta_since(self.start).as_micros() / 1_000_000;
+        if elapsed_secs < self.cliff_secs {
+            return Amount::ZERO;
+        }
+        if elapsed_secs >= self.vesting_secs {
+            return self.total;
+        }
+        let vested = u128::from(self.total).saturating_mul(u128::from(elapsed_secs))
+            / u128::from(self.vesting_secs);
+        Amount::from_attos(vested)
+    }
+
+    /// Tokens claimable at `now`.
+    pub fn claimable_at(&self, now: Timestamp) -> Amount {
+        self.vested_at(now).saturating_sub(self.claimed)
     }
 }
 
-/// Account identifier for claim-like operations.
+/// Account identifier (chain and owner) for transfer targets and claims.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Account {
     pub chain_id: linera_sdk::linera_base_types::ChainId,
@@ -69,6 +135,16 @@ pub struct TransferRequest {
     pub owner: AccountOwner,
     pub amount: Amount,
     pub target_account: Account,
+    /// Tip paid in this token (not wLin) to the block signer relaying the
+    /// transfer; skipped when the owner signs the block.
+    #[graphql(default)]
+    pub relayer_fee: Amount,
+    /// Last moment a signed transfer may execute (unchecked without a signature).
+    #[graphql(default)]
+    pub deadline: Timestamp,
+    /// Owner's current transfer nonce (unchecked without a signature).
+    #[graphql(default)]
+    pub nonce: u64,
 }
 impl<'de> BcsSignable<'de> for TransferRequest {}
 
@@ -91,15 +167,207 @@ pub struct ApproveRequest {
 }
 impl<'de> BcsSignable<'de> for ApproveRequest {}
 
+/// Permit payload (signed by owner): an approve that a spender may submit
+/// until `deadline`, once per nonce.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct PermitRequest {
+    pub owner: AccountOwner,
+    pub spender: AccountOwner,
+    pub allowance: Amount,
+    pub deadline: Timestamp,
+    pub nonce: u64,
+}
+impl<'de> BcsSignable<'de> for PermitRequest {}
+
+/// Unwrap payload (signed by owner): burns wLin and releases native tokens.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct UnwrapRequest {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+    /// Last moment a signed unwrap may execute (unchecked without a signature).
+    #[graphql(default)]
+    pub deadline: Timestamp,
+    /// Owner's current transfer nonce (unchecked without a signature).
+    #[graphql(default)]
+    pub nonce: u64,
+}
+impl<'de> BcsSignable<'de> for UnwrapRequest {}
+
+/// Single credit within a batch transfer.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct BatchRecipient {
+    pub owner: AccountOwner,
+    pub amount: Amount,
+}
+
+/// Batch transfer payload (signed by owner): same-chain credits debited once.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct BatchTransferRequest {
+    pub owner: AccountOwner,
+    pub recipients: Vec<BatchRecipient>,
+    /// Last moment a signed batch may execute (unchecked without a signature).
+    #[graphql(default)]
+    pub deadline: Timestamp,
+    /// Owner's current transfer nonce (unchecked without a signature).
+    #[graphql(default)]
+    pub nonce: u64,
+}
+impl<'de> BcsSignable<'de> for BatchTransferRequest {}
+
+impl BatchTransferRequest {
+    /// Amount debited from the owner; fails on an empty or oversized batch or
+    /// a total that does not fit an `Amount`.
+    pub fn total(&self) -> IntentsResult<Amount> {
+        if self.recipients.is_empty() || self.recipients.len() > MAX_BATCH_RECIPIENTS {
+            return Err(IntentsError::InvalidBatch);
+        }
+        self.recipients.iter().try_fold(Amount::ZERO, |total, recipient| {
+            total.try_add(recipient.amount).map_err(|_| IntentsError::InvalidBatch)
+        })
+    }
+}
+
+/// Vested-claim payload (signed by the beneficiary).
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct ClaimVestedRequest {
+    pub owner: AccountOwner,
+}
+impl<'de> BcsSignable<'de> for ClaimVestedRequest {}
+
 /// Claim payload (signed by source owner).
 #[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
 pub struct ClaimRequest {
     pub source_account: Account,
     pub amount: Amount,
     pub target_account: Account,
+    /// Last moment a signed claim may execute (unchecked without a signature).
+    #[graphql(default)]
+    pub deadline: Timestamp,
+    /// Source owner's current transfer nonce on the source chain (unchecked
+    /// without a signature).
+    #[graphql(default)]
+    pub nonce: u64,
 }
 impl<'de> BcsSignable<'de> for ClaimRequest {}
 
+impl Eip712 for Account {
+    const TYPE: &'static str = "Account(string chainId,string owner)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![encode_str(&self.chain_id.to_string()), encode_owner(&self.owner)]
+    }
+}
+
+impl Eip712 for TransferRequest {
+    const TYPE: &'static str = "TransferRequest(string owner,uint256 amount,Account targetAccount,uint256 relayerFee,uint64 deadline,uint64 nonce)Account(string chainId,string owner)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            encode_owner(&self.owner),
+            encode_amount(self.amount),
+            self.target_account.hash_struct(),
+            encode_amount(self.relayer_fee),
+            encode_uint(self.deadline.micros().into()),
+            encode_uint(self.nonce.into()),
+        ]
+    }
+}
+
+impl Eip712 for TransferFromRequest {
+    const TYPE: &'static str = "TransferFromRequest(string owner,string spender,uint256 amount,Account targetAccount)Account(string chainId,string owner)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            encode_owner(&self.owner),
+            encode_owner(&self.spender),
+            encode_amount(self.amount),
+            self.target_account.hash_struct(),
+        ]
+    }
+}
+
+impl Eip712 for ApproveRequest {
+    const TYPE: &'static str = "ApproveRequest(string owner,string spender,uint256 allowance)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            encode_owner(&self.owner),
+            encode_owner(&self.spender),
+            encode_amount(self.allowance),
+        ]
+    }
+}
+
+impl Eip712 for PermitRequest {
+    const TYPE: &'static str = "PermitRequest(string owner,string spender,uint256 allowance,uint64 deadline,uint64 nonce)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            encode_owner(&self.owner),
+            encode_owner(&self.spender),
+            encode_amount(self.allowance),
+            encode_uint(self.deadline.micros().into()),
+            encode_uint(self.nonce.into()),
+        ]
+    }
+}
+
+impl Eip712 for UnwrapRequest {
+    const TYPE: &'static str = "UnwrapRequest(string owner,uint256 amount,uint64 deadline,uint64 nonce)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            encode_owner(&self.owner),
+            encode_amount(self.amount),
+            encode_uint(self.deadline.micros().into()),
+            encode_uint(self.nonce.into()),
+        ]
+    }
+}
+
+impl Eip712 for BatchRecipient {
+    const TYPE: &'static str = "BatchRecipient(string owner,uint256 amount)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![encode_owner(&self.owner), encode_amount(self.amount)]
+    }
+}
+
+impl Eip712 for BatchTransferRequest {
+    const TYPE: &'static str = "BatchTransferRequest(string owner,BatchRecipient[] recipients,uint64 deadline,uint64 nonce)BatchRecipient(string owner,uint256 amount)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            encode_owner(&self.owner),
+            encode_array(&self.recipients),
+            encode_uint(self.deadline.micros().into()),
+            encode_uint(self.nonce.into()),
+        ]
+    }
+}
+
+impl Eip712 for ClaimVestedRequest {
+    const TYPE: &'static str = "ClaimVestedRequest(string owner)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![encode_owner(&self.owner)]
+    }
+}
+
+impl Eip712 for ClaimRequest {
+    const TYPE: &'static str = "ClaimRequest(Account sourceAccount,uint256 amount,Account targetAccount,uint64 deadline,uint64 nonce)Account(string chainId,string owner)";
+
+    fn encode_data(&self) -> Vec<[u8; 32]> {
+        vec![
+            self.source_account.hash_struct(),
+            encode_amount(self.amount),
+            self.target_account.hash_struct(),
+            encode_uint(self.deadline.micros().into()),
+            encode_uint(self.nonce.into()),
+        ]
+    }
+}
+
 /// Signed transfer request.
 #[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
 pub struct SignedTransferRequest {
@@ -121,6 +389,34 @@ pub struct SignedApproveRequest {
     pub signature_hex: String,
 }
 
+/// Signed permit request.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct SignedPermitRequest {
+    pub payload: PermitRequest,
+    pub signature_hex: String,
+}
+
+/// Signed unwrap request.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct SignedUnwrapRequest {
+    pub payload: UnwrapRequest,
+    pub signature_hex: String,
+}
+
+/// Signed batch transfer request.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct SignedBatchTransferRequest {
+    pub payload: BatchTransferRequest,
+    pub signature_hex: String,
+}
+
+/// Signed vested-claim request.
+#[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
+pub struct SignedClaimVestedRequest {
+    pub payload: ClaimVestedRequest,
+    pub signature_hex: String,
+}
+
 /// Signed claim request.
 #[derive(Clone, Debug, Deserialize, Serialize, async_graphql::InputObject)]
 pub struct SignedClaimRequest {
@@ -128,15 +424,40 @@ pub struct SignedClaimRequest {
     pub signature_hex: String,
 }
 
+/// Messages between instances of the token app on different chains.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Message {
+    /// Credits `amount` to `target`; refunded to `source` if bounced.
+    Credit {
+        target: AccountOwner,
+        amount: Amount,
+        source: AccountOwner,
+    },
+    /// Claim executed on the chain holding the source account.
+    Withdraw { request: SignedClaimRequest },
+}
+
 /// Operations executed by the fungible token app.
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
     Transfer { request: SignedTransferRequest },
     TransferFrom { request: SignedTransferFromRequest },
+    /// Credits up to `MAX_BATCH_RECIPIENTS` owners on this chain; all or nothing.
+    BatchTransfer { request: SignedBatchTransferRequest },
     Approve { request: SignedApproveRequest },
+    /// Approve submitted by a third party (e.g. the matching engine).
+    Permit { request: SignedPermitRequest },
     Claim { request: SignedClaimRequest },
-    /// Mint is restricted to minter_app_id (used for wLin faucet).
+    /// Only callable by `minter_app_id` (the wLin faucet); disabled without one.
     Mint { owner: AccountOwner, amount: Amount },
+    /// Beneficiary releases the vested part of their allocation.
+    ClaimVested { request: SignedClaimVestedRequest },
+    /// Wrapper mode: deposits native tokens of the block signer and mints wLin
+    /// to `beneficiary` (default: the signer), letting an operator fund users
+    /// without a chain of their own.
+    Wrap { amount: Amount, beneficiary: Option<AccountOwner> },
+    /// Wrapper mode: burns wLin and releases native tokens to the owner.
+    Unwrap { request: SignedUnwrapRequest },
 }
 
 /// ABI for the fungible token app.
@@ -151,3 +472,98 @@ impl ServiceAbi for FungibleTokenAbi {
     type Query = async_graphql::Request;
     type QueryResponse = async_graphql::Response;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: u64 = 1_000_000;
+
+    fn schedule(claimed: u128) -> VestingSchedule {
+        VestingSchedule {
+            total: Amount::from_tokens(1_000),
+            claimed: Amount::from_tokens(claimed),
+            start: Timestamp::from(50 * SECOND),
+            cliff_secs: 100,
+            vesting_secs: 1_000,
+        }
+    }
+
+    fn at(secs_after_start: u64) -> Timestamp {
+        Timestamp::from((50 + secs_after_start) * SECOND)
+    }
+
+    #[test]
+    fn nothing_vests_before_the_cliff() {
+        assert_eq!(schedule(0).vested_at(Timestamp::from(0)), Amount::ZERO);
+        assert_eq!(schedule(0).vested_at(at(0)), Amount::ZERO);
+        assert_eq!(schedule(0).vested_at(at(99)), Amount::ZERO);
+    }
+
+    #[test]
+    fn vesting_is_linear_from_the_start() {
+        assert_eq!(schedule(0).vested_at(at(100)), Amount::from_tokens(100));
+        assert_eq!(schedule(0).vested_at(at(500)), Amount::from_tokens(500));
+        assert_eq!(schedule(0).vested_at(at(999)), Amount::from_tokens(999));
+    }
+
+    #[test]
+    fn everything_vests_at_the_end() {
+        assert_eq!(schedule(0).vested_at(at(1_000)), Amount::from_tokens(1_000));
+        assert_eq!(schedule(0).vested_at(at(10_000)), Amount::from_tokens(1_000));
+        let instant = VestingSchedule { cliff_secs: 0, vesting_secs: 0, ..schedule(0) };
+        assert_eq!(instant.vested_at(at(0)), Amount::from_tokens(1_000));
+    }
+
+    #[test]
+    fn claimable_excludes_claimed_tokens() {
+        assert_eq!(schedule(300).claimable_at(at(500)), Amount::from_tokens(200));
+        assert_eq!(schedule(500).claimable_at(at(500)), Amount::ZERO);
+    }
+
+    fn batch(amounts: &[u128]) -> BatchTransferRequest {
+        BatchTransferRequest {
+            owner: AccountOwner::Address20([1; 20]),
+            recipients: amounts
+                .iter()
+                .map(|&attos| BatchRecipient {
+                    owner: AccountOwner::Address20([2; 20]),
+                    amount: Amount::from_attos(attos),
+                })
+                .collect(),
+            deadline: at(60),
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn batch_total_sums_recipients() {
+        assert_eq!(batch(&[1, 2, 3]).total(), Ok(Amount::from_attos(6)));
+        let full = vec![1; MAX_BATCH_RECIPIENTS];
+        assert_eq!(batch(&full).total(), Ok(Amount::from_attos(MAX_BATCH_RECIPIENTS as u128)));
+    }
+
+    #[test]
+    fn batch_total_rejects_malformed_batches() {
+        assert_eq!(batch(&[]).total(), Err(IntentsError::InvalidBatch));
+        let oversized = vec![1; MAX_BATCH_RECIPIENTS + 1];
+        assert_eq!(batch(&oversized).total(), Err(IntentsError::InvalidBatch));
+        assert_eq!(batch(&[u128::MAX, 1]).total(), Err(IntentsError::InvalidBatch));
+    }
+
+    #[test]
+    fn replayed_batch_is_rejected() {
+        let request = batch(&[5]);
+        let check = |now, expected| {
+            auth::check_fresh(now, request.deadline, request.nonce, expected)
+        };
+        // First submission consumes nonce 0; resubmitting the same signed
+        // batch then finds nonce 1 expected.
+        assert_eq!(check(at(0), 0), Ok(()));
+        assert_eq!(check(at(0), 1), Err(IntentsError::InvalidNonce));
+        assert_eq!(check(at(61), 0), Err(IntentsError::RequestExpired));
+        // The nonce is signed, so bumping it needs a fresh signature.
+        let bumped = BatchTransferRequest { nonce: 1, ..batch(&[5]) };
+        assert_ne!(request.hash_struct(), bumped.hash_struct());
+    }
+}
@@ -12,7 +12,7 @@ use linera_sdk::{
     Service, ServiceRuntime,
 };
 
-use fungible_token::{FungibleTokenAbi, Operation};
+use fungible_token::{FungibleTokenAbi, Operation, VestingSchedule};
 use fungible_token::state::FungibleState;
 
 #[derive(Clone)]
@@ -57,6 +57,16 @@ impl FungibleService {
         self.runtime.application_parameters().ticker_symbol.clone()
     }
 
+    /// Whether the token is backed 1:1 by native tokens.
+    async fn wrapped_native(&self) -> bool {
+        self.runtime.application_parameters().wrapped_native
+    }
+
+    /// Decimal places of every balance (matches `Amount`).
+    async fn decimals(&self) -> u8 {
+        shared_types::TOKEN_DECIMALS
+    }
+
     async fn balance(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> Option<linera_sdk::linera_base_types::Amount> {
         self.state
             .balances
@@ -76,4 +86,45 @@ impl FungibleService {
             .await
             .expect("Failed to read allowance")
     }
+
+    /// Locked allocation of `owner`, if any.
+    async fn vesting(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> Option<VestingSchedule> {
+        self.state
+            .vesting
+            .get(&owner)
+            .await
+            .expect("Failed to read vesting schedule")
+    }
+
+    /// Vested tokens `owner` can claim now.
+    async fn claimable_vested(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> linera_sdk::linera_base_types::Amount {
+        self.state
+            .vesting
+            .get(&owner)
+            .await
+            .expect("Failed to read vesting schedule")
+            .map(|schedule| schedule.claimable_at(self.runtime.system_time()))
+            .unwrap_or_default()
+    }
+
+    /// Nonce the owner's next permit must carry.
+    async fn permit_nonce(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> u64 {
+        self.state
+            .permit_nonces
+            .get(&owner)
+            .await
+            .expect("Failed to read permit nonce")
+            .unwrap_or_default()
+    }
+
+    /// Nonce the owner's next explicitly signed transfer, claim or unwrap
+    /// must carry.
+    async fn transfer_nonce(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> u64 {
+        self.state
+            .transfer_nonces
+            .get(&owner)
+            .await
+            .expect("Failed to read transfer nonce")
+            .unwrap_or_default()
+    }
 }
@@ -3,6 +3,8 @@
 use linera_sdk::linera_base_types::{AccountOwner, Amount};
 use linera_sdk::views::{linera_views, MapView, RootView, ViewStorageContext};
 
+use crate::VestingSchedule;
+
 /// Persistent balances and allowances.
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
@@ -11,4 +13,11 @@ pub struct FungibleState {
     pub balances: MapView<AccountOwner, Amount>,
     /// Allowances per (owner, spender).
     pub allowances: MapView<(AccountOwner, AccountOwner), Amount>,
+    /// Locked allocation per beneficiary.
+    pub vesting: MapView<AccountOwner, VestingSchedule>,
+    /// Next expected permit nonce per owner.
+    pub permit_nonces: MapView<AccountOwner, u64>,
+    /// Next expected nonce of explicitly signed transfers, claims and unwraps
+    /// per owner.
+    pub transfer_nonces: MapView<AccountOwner, u64>,
 }
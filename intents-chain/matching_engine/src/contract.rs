@@ -2,19 +2,24 @@
 
 //! Contract logic for the matching-engine app.
 
-use linera_base::crypto::AccountSignature;
 use linera_base::abi::WithContractAbi;
 use linera_base::identifiers::{ApplicationId, AccountOwner};
-use linera_sdk::linera_base_types::Amount;
+use linera_sdk::linera_base_types::{Amount, Timestamp};
 use linera_sdk::{contract::ContractRuntime, Contract, views::{RootView, View}};
 use serde_json::Value;
 
-use matching_engine::{MatchingEngineAbi, Operation};
-use matching_engine::state::{IntentStatus, MatchingEngineState};
-use shared_types::{IntentId, SignedIntent, SignedTradeRequest, TradeRequest, Side};
+use matching_engine::{
+    buy_output, circuit_breaker_tripped, fee_withdrawal, price_move_bps, sell_output, split_fee,
+    spot_price, MatchingEngineAbi, MatchingEngineResponse, Operation,
+};
+use matching_engine::state::{IntentStatus, MatchingEngineState, PriceReference};
+use shared_types::{
+    normalize_symbol, DevBuy, IntentId, IntentsError, IntentsResult, Permit, PoolConfig, Price, SignedClaimCreatorFeesRequest, SignedIntent,
+    SignedTradeRequest, SignedWithdrawIntentRequest, TradeReceipt, TradeRequest, Side,
+};
 use fungible_token::{
-    Account, FungibleTokenAbi, Operation as FungibleOperation, SignedTransferFromRequest,
-    SignedTransferRequest,
+    Account, FungibleTokenAbi, Operation as FungibleOperation, SignedPermitRequest,
+    SignedTransferFromRequest, SignedTransferRequest,
 };
 
 pub struct MatchingEngineContract {
@@ -41,22 +46,15 @@ impl Contract for MatchingEngineContract {
         MatchingEngineContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _state: Self::InstantiationArgument) {}
+    async fn instantiate(&mut self, _state: Self::InstantiationArgument) {
+        let admin = self.runtime.authenticated_signer();
+        self.state.admin.set(admin);
+    }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
-        match operation {
-            Operation::SetWlinApp { app_id } => self.set_wlin_app(app_id),
-            Operation::SetFeeDestination { owner } => self.state.fee_destination.set(Some(owner)),
-            Operation::CreatePool { symbol, token_app_id, config } => {
-                self.create_pool(symbol, token_app_id, config).await
-            }
-            Operation::Buy { trade } => self.execute_buy(trade).await,
-            Operation::Sell { trade } => self.execute_sell(trade).await,
-            Operation::PlaceIntent { intent } => self.place_intent(intent).await,
-            Operation::SettleIntent { intent_id, fill_amount } => {
-                self.settle_intent(intent_id, fill_amount).await
-            }
-        }
+        self.handle_operation(operation)
+            .await
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 
     async fn execute_message(&mut self, _message: Self::Message) {}
@@ -67,17 +65,143 @@ impl Contract for MatchingEngineContract {
 }
 
 impl MatchingEngineContract {
-    fn set_wlin_app(&mut self, app_id: String) {
-        let parsed = app_id.parse::<ApplicationId>().expect("Invalid application id");
+    async fn handle_operation(
+        &mut self,
+        operation: Operation,
+    ) -> IntentsResult<MatchingEngineResponse> {
+        match operation {
+            Operation::SetWlinApp { app_id } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.set_wlin_app(app_id)?;
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::SetFeeDestination { owner } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.fee_destination.set(Some(owner));
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::WithdrawFees { amount } => self.withdraw_fees(amount),
+            Operation::SetTokenFactory { app_id } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                let parsed = app_id
+                    .parse::<ApplicationId>()
+                    .map_err(|_| IntentsError::InvalidApplicationId)?;
+                self.state.token_factory_app_id.set(Some(parsed));
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::CreatePool { symbol, token_app_id, config, creator, dev_buy } => {
+                self.ensure_token_factory()?;
+                let symbol = normalize_symbol(&symbol);
+                self.create_pool(symbol.clone(), token_app_id, config, creator).await?;
+                let dev_buy = match dev_buy {
+                    Some(dev_buy) => Some(self.execute_dev_buy(symbol, creator, dev_buy).await?),
+                    None => None,
+                };
+                Ok(MatchingEngineResponse::PoolCreated { dev_buy })
+            }
+            Operation::Buy { trade } => {
+                Ok(MatchingEngineResponse::Trade(self.execute_buy(trade).await?))
+            }
+            Operation::Sell { trade } => {
+                Ok(MatchingEngineResponse::Trade(self.execute_sell(trade).await?))
+            }
+            Operation::PlaceIntent { intent } => Ok(MatchingEngineResponse::IntentPlaced {
+                id: self.place_intent(intent).await?,
+            }),
+            Operation::SettleIntent { intent_id, fill_amount } => {
+                let (receipt, remaining) = self.settle_intent(intent_id, fill_amount).await?;
+                Ok(MatchingEngineResponse::IntentSettled { id: intent_id, receipt, remaining })
+            }
+            Operation::ClaimCreatorFees { request } => self.claim_creator_fees(request).await,
+            Operation::SetPaused { paused } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.paused.set(paused);
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::SetPoolPaused { symbol, paused } => {
+                let symbol = normalize_symbol(&symbol);
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state
+                    .paused_symbols
+                    .insert(&symbol, paused)
+                    .expect("Failed to update pool pause flag");
+                if !paused {
+                    // Restart the breaker's reference from the current price.
+                    self.state
+                        .block_reference_price
+                        .remove(&symbol)
+                        .expect("Failed to reset reference price");
+                }
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::SetCircuitBreaker { max_move_bps } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.circuit_breaker_bps.set(max_move_bps);
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::WithdrawIntent { request } => self.withdraw_intent(request).await,
+            Operation::UpdateCreator { symbol, creator } => {
+                let symbol = normalize_symbol(&symbol);
+                self.ensure_token_factory()?;
+                if self
+                    .state
+                    .creator_by_symbol
+                    .get(&symbol)
+                    .await
+                    .expect("Failed to read creator")
+                    .is_none()
+                {
+                    return Err(IntentsError::PoolNotFound);
+                }
+                self.state
+                    .creator_by_symbol
+                    .insert(&symbol, creator)
+                    .expect("Failed to update creator");
+                Ok(MatchingEngineResponse::Ok)
+            }
+            Operation::SetLaunchProtection { symbol, protection } => {
+                let symbol = normalize_symbol(&symbol);
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                let mut config = self
+                    .state
+                    .pools
+                    .get(&symbol)
+                    .await
+                    .expect("Failed to read pool config")
+                    .ok_or(IntentsError::PoolNotFound)?;
+                config.launch_protection = protection;
+                self.state
+                    .pools
+                    .insert(&symbol, config)
+                    .expect("Failed to update pool config");
+                Ok(MatchingEngineResponse::Ok)
+            }
+        }
+    }
+
+    fn set_wlin_app(&mut self, app_id: String) -> IntentsResult<()> {
+        let parsed = app_id
+            .parse::<ApplicationId>()
+            .map_err(|_| IntentsError::InvalidApplicationId)?;
         self.state.wlin_app_id.set(Some(parsed));
+        Ok(())
     }
 
-    async fn create_pool(&mut self, symbol: String, token_app_id: String, config: shared_types::PoolConfig) {
+    async fn create_pool(
+        &mut self,
+        symbol: String,
+        token_app_id: String,
+        config: shared_types::PoolConfig,
+        creator: AccountOwner,
+    ) -> IntentsResult<()> {
         if self.state.pools.get(&symbol).await.expect("Failed to read pools").is_some() {
-            panic!("Pool already exists");
+            return Err(IntentsError::PoolAlreadyExists);
+        }
+        if config.creator_fee_bps > config.fee_bps {
+            return Err(IntentsError::InvalidFeeConfig);
         }
         let token_app_id: ApplicationId = serde_json::from_value(Value::String(token_app_id))
-            .expect("Invalid token app id");
+            .map_err(|_| IntentsError::InvalidApplicationId)?;
         self.state
             .token_app_id_by_symbol
             .insert(&symbol, token_app_id)
@@ -98,49 +222,110 @@ impl MatchingEngineContract {
             .lp_enabled
             .insert(&symbol, false)
             .expect("Failed to set LP flag");
+        self.state
+            .creator_by_symbol
+            .insert(&symbol, creator)
+            .expect("Failed to insert creator");
+        let now = self.runtime.system_time();
+        self.state
+            .pool_created_at
+            .insert(&symbol, now)
+            .expect("Failed to insert pool creation time");
+        Ok(())
     }
 
-    async fn execute_buy(&mut self, trade: SignedTradeRequest) {
-        let owner = self.verify_signature(&trade.payload, &trade.signature_hex);
-        if trade.payload.owner != owner {
-            panic!("Signature owner mismatch");
+    async fn execute_buy(&mut self, trade: SignedTradeRequest) -> IntentsResult<TradeReceipt> {
+        auth::authenticate_typed(
+            &mut self.runtime,
+            &trade.payload,
+            trade.payload.owner,
+            &trade.signature_hex,
+            None,
+        )?;
+        self.consume_trade_nonce(
+            trade.payload.owner,
+            &trade.signature_hex,
+            trade.payload.deadline,
+            trade.payload.nonce,
+        )
+        .await?;
+        if let Some(permit) = trade.permit {
+            self.apply_permit(&trade.payload.symbol, Side::Buy, trade.payload.owner, permit)
+                .await?;
         }
-        self.execute_trade_with_transfer(trade.payload, trade.signature_hex, true).await;
+        self.pay_relayer_fee(trade.payload.owner, trade.payload.relayer_fee)?;
+        self.execute_trade_with_transfer(trade.payload, true).await
     }
 
-    async fn execute_sell(&mut self, trade: SignedTradeRequest) {
-        let owner = self.verify_signature(&trade.payload, &trade.signature_hex);
-        if trade.payload.owner != owner {
-            panic!("Signature owner mismatch");
+    async fn execute_sell(&mut self, trade: SignedTradeRequest) -> IntentsResult<TradeReceipt> {
+        auth::authenticate_typed(
+            &mut self.runtime,
+            &trade.payload,
+            trade.payload.owner,
+            &trade.signature_hex,
+            None,
+        )?;
+        self.consume_trade_nonce(
+            trade.payload.owner,
+            &trade.signature_hex,
+            trade.payload.deadline,
+            trade.payload.nonce,
+        )
+        .await?;
+        if let Some(permit) = trade.permit {
+            self.apply_permit(&trade.payload.symbol, Side::Sell, trade.payload.owner, permit)
+                .await?;
         }
-        self.execute_trade_with_transfer(trade.payload, trade.signature_hex, false).await;
+        self.pay_relayer_fee(trade.payload.owner, trade.payload.relayer_fee)?;
+        self.execute_trade_with_transfer(trade.payload, false).await
     }
 
-    async fn place_intent(&mut self, intent: SignedIntent) {
-        let owner = self.verify_signature(&intent.payload, &intent.signature_hex);
-        if intent.payload.owner != owner {
-            panic!("Signature owner mismatch");
-        }
-        let symbol = intent.payload.symbol.clone();
-        let amount = intent.payload.amount;
+    async fn place_intent(&mut self, intent: SignedIntent) -> IntentsResult<IntentId> {
+        auth::authenticate_typed(
+            &mut self.runtime,
+            &intent.payload,
+            intent.payload.owner,
+            &intent.signature_hex,
+            None,
+        )?;
+        self.consume_trade_nonce(
+            intent.payload.owner,
+            &intent.signature_hex,
+            intent.payload.deadline,
+            intent.payload.nonce,
+        )
+        .await?;
+        let mut payload = intent.payload;
+        payload.symbol = normalize_symbol(&payload.symbol);
+        let symbol = payload.symbol.clone();
+        let amount = payload.amount;
         if amount == Amount::ZERO {
-            panic!("Intent amount must be > 0");
+            return Err(IntentsError::ZeroAmount);
+        }
+        if payload.limit_price.is_zero() {
+            return Err(IntentsError::InvalidPrice);
+        }
+        if self.state.pools.get(&symbol).await.expect("Failed to read pools").is_none() {
+            return Err(IntentsError::PoolNotFound);
+        }
+        if self.is_paused(&symbol).await {
+            return Err(IntentsError::TradingPaused);
         }
+        if let Some(permit) = intent.permit {
+            self.apply_permit(&symbol, payload.side, payload.owner, permit)
+                .await?;
+        }
+        self.pay_relayer_fee(payload.owner, payload.relayer_fee)?;
 
         // Escrow: move assets into the matching-engine app account.
-        self.transfer_in_intent(&intent.payload, &intent.signature_hex, amount).await;
+        self.pull_from_owner(&symbol, payload.side, payload.owner, amount)
+            .await?;
 
-        let next = self
-            .state
-            .next_intent_id
-            .get(&symbol)
-            .await
-            .expect("Failed to read next_intent_id")
-            .unwrap_or_default();
-        let intent_id = IntentId(next + 1);
+        let next = *self.state.next_intent_id.get() + 1;
+        let intent_id = IntentId(next);
         self.state
             .intents
-            .insert(&intent_id, intent.payload)
+            .insert(&intent_id, payload)
             .expect("Failed to insert intent");
         self.state
             .intent_status
@@ -154,22 +339,24 @@ impl MatchingEngineContract {
             .intent_escrowed
             .insert(&intent_id, amount)
             .expect("Failed to insert intent escrowed");
-        self.state
-            .next_intent_id
-            .insert(&symbol, next + 1)
-            .expect("Failed to update next_intent_id");
+        self.state.next_intent_id.set(next);
+        Ok(intent_id)
     }
 
-    async fn settle_intent(&mut self, intent_id: IntentId, fill_amount: Amount) {
+    async fn settle_intent(
+        &mut self,
+        intent_id: IntentId,
+        fill_amount: Amount,
+    ) -> IntentsResult<(Option<TradeReceipt>, Amount)> {
         let status = self
             .state
             .intent_status
             .get(&intent_id)
             .await
             .expect("Failed to read intent status")
-            .expect("Intent not found");
-        if status == IntentStatus::Filled {
-            return;
+            .ok_or(IntentsError::IntentNotFound)?;
+        if status == IntentStatus::Filled || status == IntentStatus::Cancelled {
+            return Ok((None, Amount::ZERO));
         }
 
         let intent = self
@@ -178,7 +365,7 @@ impl MatchingEngineContract {
             .get(&intent_id)
             .await
             .expect("Failed to read intent")
-            .expect("Intent not found");
+            .ok_or(IntentsError::IntentNotFound)?;
         let remaining = self
             .state
             .intent_remaining
@@ -191,7 +378,7 @@ impl MatchingEngineContract {
                 .intent_status
                 .insert(&intent_id, IntentStatus::Filled)
                 .expect("Failed to update intent status");
-            return;
+            return Ok((None, Amount::ZERO));
         }
         let fill = if fill_amount == Amount::ZERO {
             remaining
@@ -199,14 +386,11 @@ impl MatchingEngineContract {
             std::cmp::min(fill_amount, remaining)
         };
 
-        let price = self.current_price(&intent.symbol).await;
-        let limit = intent
-            .limit_price
-            .parse::<Amount>()
-            .expect("Invalid limit price format");
+        let price = self.current_price(&intent.symbol).await?;
+        let limit = intent.limit_price;
         let is_buy = intent.side == Side::Buy;
         if (is_buy && price > limit) || (!is_buy && price < limit) {
-            panic!("Limit price not satisfied");
+            return Err(IntentsError::LimitPriceNotSatisfied);
         }
 
         let trade = TradeRequest {
@@ -215,8 +399,11 @@ impl MatchingEngineContract {
             amount: fill,
             min_out: Amount::ZERO,
             owner: intent.owner,
+            relayer_fee: Amount::ZERO,
+            deadline: Timestamp::from(0),
+            nonce: 0,
         };
-        self.execute_trade_from_escrow(trade, is_buy).await;
+        let receipt = self.execute_trade_from_escrow(trade, is_buy, true).await?;
 
         let new_remaining = remaining.saturating_sub(fill);
         self.state
@@ -236,15 +423,53 @@ impl MatchingEngineContract {
             .intent_status
             .insert(&intent_id, new_status)
             .expect("Failed to update intent status");
+        Ok((Some(receipt), new_remaining))
     }
 
-    async fn execute_trade_with_transfer(&mut self, trade: TradeRequest, signature_hex: String, is_buy: bool) {
+    async fn execute_trade_with_transfer(
+        &mut self,
+        mut trade: TradeRequest,
+        is_buy: bool,
+    ) -> IntentsResult<TradeReceipt> {
+        trade.symbol = normalize_symbol(&trade.symbol);
         // Transfer input asset from user into app custody first.
-        self.transfer_in_trade(&trade, &signature_hex, trade.amount).await;
-        self.execute_trade_from_escrow(trade, is_buy).await;
+        self.pull_from_owner(&trade.symbol, trade.side, trade.owner, trade.amount)
+            .await?;
+        self.execute_trade_from_escrow(trade, is_buy, true).await
+    }
+
+    /// Executes the creator's buy as the first trade of a freshly created pool.
+    async fn execute_dev_buy(
+        &mut self,
+        symbol: String,
+        creator: AccountOwner,
+        dev_buy: DevBuy,
+    ) -> IntentsResult<TradeReceipt> {
+        if dev_buy.amount == Amount::ZERO {
+            return Err(IntentsError::ZeroAmount);
+        }
+        let trade = TradeRequest {
+            owner: creator,
+            symbol,
+            side: Side::Buy,
+            amount: dev_buy.amount,
+            min_out: dev_buy.min_out,
+            relayer_fee: Amount::ZERO,
+            deadline: Timestamp::from(0),
+            nonce: 0,
+        };
+        self.pull_from_owner(&trade.symbol, trade.side, trade.owner, trade.amount)
+            .await?;
+        // Launch protections guard against snipers, not the creator's own stake.
+        self.execute_trade_from_escrow(trade, true, false).await
     }
 
-    async fn execute_trade_from_escrow(&mut self, trade: TradeRequest, is_buy: bool) {
+    async fn execute_trade_from_escrow(
+        &mut self,
+        trade: TradeRequest,
+        is_buy: bool,
+        enforce_launch_protection: bool,
+    ) -> IntentsResult<TradeReceipt> {
         let symbol = trade.symbol.clone();
         let config = self
             .state
@@ -252,7 +477,10 @@ impl MatchingEngineContract {
             .get(&symbol)
             .await
             .expect("Failed to read pool config")
-            .expect("Pool not found");
+            .ok_or(IntentsError::PoolNotFound)?;
+        if self.is_paused(&symbol).await {
+            return Err(IntentsError::TradingPaused);
+        }
 
         let mut wlin = self
             .state
@@ -269,41 +497,20 @@ impl MatchingEngineContract {
             .expect("Failed to read token reserve")
             .unwrap_or_default();
 
+        let price_before = spot_price(&config, wlin, token);
+        let reference_price = self.block_reference_price(&symbol, price_before).await;
+
         let fee_bps = config.fee_bps as u128;
         let amount_in = trade.amount;
 
         let (wlin_out, token_out, fee_amount) = if is_buy {
             let dx = amount_in.to_attos();
             let fee = dx.saturating_mul(fee_bps).saturating_div(10_000);
-            let dx_after_fee = dx.saturating_sub(fee);
-
-            let base_attos = wlin.to_attos();
-            let token_attos = token.to_attos();
-            let v_x = config.v_x.to_attos();
-            let v_y = config.v_y.to_attos();
-            let k = base_attos
-                .saturating_add(v_x)
-                .saturating_mul(token_attos.saturating_add(v_y));
-
-            let new_token_attos = k
-                .saturating_div(base_attos.saturating_add(v_x).saturating_add(dx_after_fee))
-                .saturating_sub(v_y);
-            let y_out = token_attos.saturating_sub(new_token_attos);
-            (Amount::ZERO, Amount::from_attos(y_out), Amount::from_attos(fee))
+            let dx_after_fee = Amount::from_attos(dx.saturating_sub(fee));
+            let y_out = buy_output(&config, wlin, token, dx_after_fee);
+            (Amount::ZERO, y_out, Amount::from_attos(fee))
         } else {
-            let dy = amount_in.to_attos();
-            let base_attos = wlin.to_attos();
-            let token_attos = token.to_attos();
-            let v_x = config.v_x.to_attos();
-            let v_y = config.v_y.to_attos();
-            let k = base_attos
-                .saturating_add(v_x)
-                .saturating_mul(token_attos.saturating_add(v_y));
-
-            let new_base_attos = k
-                .saturating_div(token_attos.saturating_add(v_y).saturating_add(dy))
-                .saturating_sub(v_x);
-            let x_out = base_attos.saturating_sub(new_base_attos);
+            let x_out = sell_output(&config, wlin, token, amount_in).to_attos();
             let fee = x_out.saturating_mul(fee_bps).saturating_div(10_000);
             let x_out_after_fee = x_out.saturating_sub(fee);
             (Amount::from_attos(x_out_after_fee), Amount::ZERO, Amount::from_attos(fee))
@@ -311,19 +518,30 @@ impl MatchingEngineContract {
 
         if is_buy {
             if token_out < trade.min_out {
-                panic!("Min out not satisfied");
+                return Err(IntentsError::MinOutNotSatisfied);
             }
             let dx = amount_in.to_attos();
             let fee = dx.saturating_mul(fee_bps).saturating_div(10_000);
             let dx_after_fee = dx.saturating_sub(fee);
             wlin = Amount::from_attos(wlin.to_attos().saturating_add(dx_after_fee));
             token = Amount::from_attos(token.to_attos().saturating_sub(token_out.to_attos()));
+            if enforce_launch_protection {
+                self.enforce_launch_protection(
+                    &symbol,
+                    &config,
+                    trade.owner,
+                    amount_in,
+                    price_before,
+                    spot_price(&config, wlin, token),
+                )
+                .await?;
+            }
 
             // Send tokens from app custody to user.
-            self.transfer_out_token(&trade.symbol, trade.owner, token_out).await;
+            self.transfer_out_token(&trade.symbol, trade.owner, token_out).await?;
         } else {
             if wlin_out < trade.min_out {
-                panic!("Min out not satisfied");
+                return Err(IntentsError::MinOutNotSatisfied);
             }
             let base_attos = wlin.to_attos();
             let token_attos = token.to_attos();
@@ -336,14 +554,27 @@ impl MatchingEngineContract {
             token = Amount::from_attos(token_attos.saturating_add(dy));
 
             // Send wLin from app custody to user.
-            self.transfer_out_wlin(trade.owner, wlin_out).await;
+            self.transfer_out_wlin(trade.owner, wlin_out)?;
         }
 
-        // Credit fees to the operator fee destination in wLin.
-        if let Some(fee_dest) = self.state.fee_destination.get().clone() {
-            if fee_amount > Amount::ZERO {
-                self.transfer_out_wlin(fee_dest, fee_amount).await;
-            }
+        // Accrue the creator's share; the rest goes to the operator fee destination in wLin.
+        let (creator_fee, protocol_fee) = split_fee(&config, fee_amount);
+        if creator_fee > Amount::ZERO {
+            let accrued = self
+                .state
+                .creator_fees
+                .get(&symbol)
+                .await
+                .expect("Failed to read creator fees")
+                .unwrap_or_default();
+            self.state
+                .creator_fees
+                .insert(&symbol, accrued.saturating_add(creator_fee))
+                .expect("Failed to update creator fees");
+        }
+        if protocol_fee > Amount::ZERO {
+            let accrued = *self.state.protocol_fees.get();
+            self.state.protocol_fees.set(accrued.saturating_add(protocol_fee));
         }
 
         self.state
@@ -361,152 +592,379 @@ impl MatchingEngineContract {
                 .insert(&symbol, true)
                 .expect("Failed to enable LPs");
         }
+
+        self.check_circuit_breaker(&symbol, reference_price, spot_price(&config, wlin, token));
+
+        Ok(TradeReceipt {
+            symbol,
+            side: trade.side,
+            amount_in,
+            amount_out: if is_buy { token_out } else { wlin_out },
+            fee: fee_amount,
+            wlin_reserve: wlin,
+            token_reserve: token,
+        })
     }
 
-    async fn transfer_in_trade(&mut self, trade: &TradeRequest, signature_hex: &str, amount: Amount) {
-        let app_owner: AccountOwner = self.runtime.application_id().into();
-        if trade.side == Side::Buy {
-            let wlin = self
-                .state
-                .wlin_app_id
-                .get()
-                .clone()
-                .expect("wLin app id not set")
-                .with_abi::<FungibleTokenAbi>();
-            let op = FungibleOperation::TransferFrom {
-                request: SignedTransferFromRequest {
-                    payload: fungible_token::TransferFromRequest {
-                        owner: trade.owner,
-                        spender: app_owner,
-                        amount,
-                        target_account: Account {
-                            chain_id: self.runtime.chain_id(),
-                            owner: app_owner,
-                        },
-                    },
-                    signature_hex: String::new(),
-                },
-            };
-            self.runtime.call_application(true, wlin, &op);
-        } else {
-            let token_app_id = self
+    async fn withdraw_intent(
+        &mut self,
+        request: SignedWithdrawIntentRequest,
+    ) -> IntentsResult<MatchingEngineResponse> {
+        let owner = auth::authenticate(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
+        let intent_id = request.payload.intent_id;
+        let intent = self
+            .state
+            .intents
+            .get(&intent_id)
+            .await
+            .expect("Failed to read intent")
+            .ok_or(IntentsError::IntentNotFound)?;
+        if intent.owner != owner {
+            return Err(IntentsError::NotIntentOwner);
+        }
+        let status = self
+            .state
+            .intent_status
+            .get(&intent_id)
+            .await
+            .expect("Failed to read intent status")
+            .ok_or(IntentsError::IntentNotFound)?;
+        if status == IntentStatus::Filled || status == IntentStatus::Cancelled {
+            return Err(IntentsError::IntentClosed);
+        }
+        if !self.is_paused(&intent.symbol).await {
+            return Err(IntentsError::PoolNotPaused);
+        }
+
+        let escrowed = self
+            .state
+            .intent_escrowed
+            .get(&intent_id)
+            .await
+            .expect("Failed to read intent escrowed")
+            .unwrap_or_default();
+        if escrowed > Amount::ZERO {
+            match intent.side {
+                Side::Buy => self.transfer_out_wlin(owner, escrowed)?,
+                Side::Sell => self.transfer_out_token(&intent.symbol, owner, escrowed).await?,
+            }
+        }
+        self.state
+            .intent_escrowed
+            .insert(&intent_id, Amount::ZERO)
+            .expect("Failed to update intent escrowed");
+        self.state
+            .intent_remaining
+            .insert(&intent_id, Amount::ZERO)
+            .expect("Failed to update intent remaining");
+        self.state
+            .intent_status
+            .insert(&intent_id, IntentStatus::Cancelled)
+            .expect("Failed to update intent status");
+        Ok(MatchingEngineResponse::IntentWithdrawn { id: intent_id, amount: escrowed })
+    }
+
+    /// Applies the pool's launch protections to a buy of `amount_in` wLin that
+    /// moves the spot price from `price_before` to `price_after`.
+    async fn enforce_launch_protection(
+        &mut self,
+        symbol: &str,
+        config: &PoolConfig,
+        owner: AccountOwner,
+        amount_in: Amount,
+        price_before: Price,
+        price_after: Price,
+    ) -> IntentsResult<()> {
+        let protection = &config.launch_protection;
+        let Some(created_at) = self
+            .state
+            .pool_created_at
+            .get(symbol)
+            .await
+            .expect("Failed to read pool creation time")
+        else {
+            return Ok(());
+        };
+        let now = self.runtime.system_time();
+        if !protection.is_protected(created_at, now)? {
+            return Ok(());
+        }
+        let key = (symbol.to_string(), owner);
+        let bought = self
+            .state
+            .launch_bought
+            .get(&key)
+            .await
+            .expect("Failed to read launch purchases")
+            .unwrap_or_default();
+        let impact_bps = price_move_bps(price_before, price_after);
+        let bought = protection.check_buy(bought, amount_in, impact_bps)?;
+        if protection.max_buy_per_owner > Amount::ZERO {
+            self.state
+                .launch_bought
+                .insert(&key, bought)
+                .expect("Failed to update launch purchases");
+        }
+        Ok(())
+    }
+
+    /// True when trading is halted globally or on `symbol`.
+    async fn is_paused(&self, symbol: &str) -> bool {
+        *self.state.paused.get()
+            || self
                 .state
-                .token_app_id_by_symbol
-                .get(&trade.symbol)
+                .paused_symbols
+                .get(symbol)
                 .await
-                .expect("Failed to read token app id")
-                .expect("Token app id not found")
-                .with_abi::<FungibleTokenAbi>();
-            let op = FungibleOperation::TransferFrom {
-                request: SignedTransferFromRequest {
-                    payload: fungible_token::TransferFromRequest {
-                        owner: trade.owner,
-                        spender: app_owner,
-                        amount,
-                        target_account: Account {
-                            chain_id: self.runtime.chain_id(),
-                            owner: app_owner,
-                        },
-                    },
-                    signature_hex: String::new(),
-                },
-            };
-            self.runtime.call_application(true, token_app_id, &op);
+                .expect("Failed to read pool pause flag")
+                .unwrap_or(false)
+    }
+
+    /// Returns the spot price at the start of the current block for `symbol`,
+    /// recording `current` as the reference on the first trade of a block.
+    async fn block_reference_price(&mut self, symbol: &str, current: Price) -> Price {
+        let height = self.runtime.block_height();
+        match self
+            .state
+            .block_reference_price
+            .get(symbol)
+            .await
+            .expect("Failed to read reference price")
+        {
+            Some(reference) if reference.height == height => reference.price,
+            _ => {
+                self.state
+                    .block_reference_price
+                    .insert(symbol, PriceReference { height, price: current })
+                    .expect("Failed to record reference price");
+                current
+            }
+        }
+    }
+
+    /// Halts the pool when the spot price moved more than the configured
+    /// threshold since the start of the block. The triggering trade still
+    /// settles; later trades fail until the admin resumes the pool.
+    fn check_circuit_breaker(&mut self, symbol: &str, reference: Price, current: Price) {
+        let max_move_bps = *self.state.circuit_breaker_bps.get();
+        if circuit_breaker_tripped(max_move_bps, reference, current) {
+            self.state
+                .paused_symbols
+                .insert(symbol, true)
+                .expect("Failed to trip circuit breaker");
+        }
+    }
+
+    fn withdraw_fees(&mut self, amount: Option<Amount>) -> IntentsResult<MatchingEngineResponse> {
+        let admin = auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+        let accrued = *self.state.protocol_fees.get();
+        let amount = fee_withdrawal(accrued, amount)?;
+        let to = self.state.fee_destination.get().unwrap_or(admin);
+        if amount > Amount::ZERO {
+            self.transfer_out_wlin(to, amount)?;
+            self.state.protocol_fees.set(accrued.saturating_sub(amount));
+        }
+        Ok(MatchingEngineResponse::FeesWithdrawn { to, amount })
+    }
+
+    /// Fails unless the caller is the registered token-factory app.
+    fn ensure_token_factory(&mut self) -> IntentsResult<()> {
+        let factory = *self.state.token_factory_app_id.get();
+        if factory.is_none() || self.runtime.authenticated_caller_id() != factory {
+            return Err(IntentsError::Unauthorized);
         }
+        Ok(())
     }
 
-    async fn transfer_in_intent(
+    async fn claim_creator_fees(
         &mut self,
-        intent: &shared_types::Intent,
-        signature_hex: &str,
+        request: SignedClaimCreatorFeesRequest,
+    ) -> IntentsResult<MatchingEngineResponse> {
+        let owner = auth::authenticate(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
+        let symbol = normalize_symbol(&request.payload.symbol);
+        let creator = self
+            .state
+            .creator_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read creator")
+            .ok_or(IntentsError::PoolNotFound)?;
+        if creator != owner {
+            return Err(IntentsError::NotCreator);
+        }
+        let amount = self
+            .state
+            .creator_fees
+            .get(&symbol)
+            .await
+            .expect("Failed to read creator fees")
+            .unwrap_or_default();
+        if amount > Amount::ZERO {
+            self.transfer_out_wlin(owner, amount)?;
+            self.state
+                .creator_fees
+                .insert(&symbol, Amount::ZERO)
+                .expect("Failed to reset creator fees");
+        }
+        Ok(MatchingEngineResponse::CreatorFeesClaimed { symbol, amount })
+    }
+
+    /// Moves the input asset of a trade (wLin for buys, the token for sells)
+    /// from `owner` into app custody using the owner's allowance.
+    async fn pull_from_owner(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        owner: AccountOwner,
         amount: Amount,
-    ) {
+    ) -> IntentsResult<()> {
         let app_owner: AccountOwner = self.runtime.application_id().into();
-        if intent.side == Side::Buy {
-            let wlin = self
-                .state
-                .wlin_app_id
-                .get()
-                .clone()
-                .expect("wLin app id not set")
-                .with_abi::<FungibleTokenAbi>();
-            let op = FungibleOperation::TransferFrom {
-                request: SignedTransferFromRequest {
-                    payload: fungible_token::TransferFromRequest {
-                        owner: intent.owner,
-                        spender: app_owner,
-                        amount,
-                        target_account: Account {
-                            chain_id: self.runtime.chain_id(),
-                            owner: app_owner,
-                        },
-                    },
-                    signature_hex: String::new(),
-                },
-            };
-            self.runtime.call_application(true, wlin, &op);
-        } else {
-            let token_app_id = self
-                .state
-                .token_app_id_by_symbol
-                .get(&intent.symbol)
-                .await
-                .expect("Failed to read token app id")
-                .expect("Token app id not found")
-                .with_abi::<FungibleTokenAbi>();
-            let op = FungibleOperation::TransferFrom {
-                request: SignedTransferFromRequest {
-                    payload: fungible_token::TransferFromRequest {
-                        owner: intent.owner,
-                        spender: app_owner,
-                        amount,
-                        target_account: Account {
-                            chain_id: self.runtime.chain_id(),
-                            owner: app_owner,
-                        },
-                    },
-                    signature_hex: String::new(),
+        let asset = match side {
+            Side::Buy => self.wlin_app()?,
+            Side::Sell => self.token_app(symbol).await?,
+        };
+        self.transfer_from_owner(asset, owner, app_owner, amount);
+        Ok(())
+    }
+
+    /// Forwards `permit` to the asset app `side` pulls from, with this app as spender.
+    async fn apply_permit(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        owner: AccountOwner,
+        permit: Permit,
+    ) -> IntentsResult<()> {
+        permit.ensure_signed()?;
+        let asset = match side {
+            Side::Buy => self.wlin_app()?,
+            Side::Sell => self.token_app(&normalize_symbol(symbol)).await?,
+        };
+        let op = FungibleOperation::Permit {
+            request: SignedPermitRequest {
+                payload: fungible_token::PermitRequest {
+                    owner,
+                    spender: self.runtime.application_id().into(),
+                    allowance: permit.allowance,
+                    deadline: permit.deadline,
+                    nonce: permit.nonce,
                 },
-            };
-            self.runtime.call_application(true, token_app_id, &op);
+                signature_hex: permit.signature_hex,
+            },
+        };
+        self.runtime.call_application(true, asset, &op);
+        Ok(())
+    }
+
+    /// Consumes `owner`'s trade nonce for a request carrying its own signature.
+    ///
+    /// Block-signed requests cannot be replayed by a relayer and skip the check.
+    async fn consume_trade_nonce(
+        &mut self,
+        owner: AccountOwner,
+        signature_hex: &str,
+        deadline: Timestamp,
+        nonce: u64,
+    ) -> IntentsResult<()> {
+        if signature_hex.is_empty() {
+            return Ok(());
+        }
+        let expected = self
+            .state
+            .trade_nonces
+            .get(&owner)
+            .await
+            .expect("Failed to read trade nonce")
+            .unwrap_or_default();
+        auth::ensure_fresh(&mut self.runtime, deadline, nonce, expected)?;
+        self.state
+            .trade_nonces
+            .insert(&owner, expected + 1)
+            .expect("Failed to update trade nonce");
+        Ok(())
+    }
+
+    /// Pays `fee` wLin from `owner` to the block signer relaying the request.
+    ///
+    /// Nothing is charged when the owner signs the block themselves.
+    fn pay_relayer_fee(&mut self, owner: AccountOwner, fee: Amount) -> IntentsResult<()> {
+        if fee == Amount::ZERO {
+            return Ok(());
+        }
+        let Some(relayer) = self.runtime.authenticated_signer() else {
+            return Ok(());
+        };
+        if relayer == owner {
+            return Ok(());
         }
+        let wlin = self.wlin_app()?;
+        self.transfer_from_owner(wlin, owner, relayer, fee);
+        Ok(())
     }
 
-    async fn transfer_out_wlin(&mut self, owner: AccountOwner, amount: Amount) {
+    /// Moves `amount` of `asset` from `owner` to `to` using this app's allowance.
+    fn transfer_from_owner(
+        &mut self,
+        asset: ApplicationId<FungibleTokenAbi>,
+        owner: AccountOwner,
+        to: AccountOwner,
+        amount: Amount,
+    ) {
         let app_owner: AccountOwner = self.runtime.application_id().into();
-        let wlin = self
-            .state
-            .wlin_app_id
-            .get()
-            .clone()
-            .expect("wLin app id not set")
-            .with_abi::<FungibleTokenAbi>();
-        let op = FungibleOperation::Transfer {
-            request: SignedTransferRequest {
-                payload: fungible_token::TransferRequest {
-                    owner: app_owner,
+        let op = FungibleOperation::TransferFrom {
+            request: SignedTransferFromRequest {
+                payload: fungible_token::TransferFromRequest {
+                    owner,
+                    spender: app_owner,
                     amount,
                     target_account: Account {
                         chain_id: self.runtime.chain_id(),
-                        owner,
+                        owner: to,
                     },
                 },
                 signature_hex: String::new(),
             },
         };
-        self.runtime.call_application(true, wlin, &op);
+        self.runtime.call_application(true, asset, &op);
+    }
+
+    fn transfer_out_wlin(&mut self, owner: AccountOwner, amount: Amount) -> IntentsResult<()> {
+        let wlin = self.wlin_app()?;
+        self.transfer_out(wlin, owner, amount);
+        Ok(())
     }
 
-    async fn transfer_out_token(&mut self, symbol: &str, owner: AccountOwner, amount: Amount) {
+    async fn transfer_out_token(
+        &mut self,
+        symbol: &str,
+        owner: AccountOwner,
+        amount: Amount,
+    ) -> IntentsResult<()> {
+        let token_app_id = self.token_app(symbol).await?;
+        self.transfer_out(token_app_id, owner, amount);
+        Ok(())
+    }
+
+    /// Sends `amount` of `asset` from app custody to `owner` on this chain.
+    fn transfer_out(
+        &mut self,
+        asset: ApplicationId<FungibleTokenAbi>,
+        owner: AccountOwner,
+        amount: Amount,
+    ) {
         let app_owner: AccountOwner = self.runtime.application_id().into();
-        let token_app_id = self
-            .state
-            .token_app_id_by_symbol
-            .get(symbol)
-            .await
-            .expect("Failed to read token app id")
-            .expect("Token app id not found")
-            .with_abi::<FungibleTokenAbi>();
         let op = FungibleOperation::Transfer {
             request: SignedTransferRequest {
                 payload: fungible_token::TransferRequest {
@@ -516,57 +974,57 @@ impl MatchingEngineContract {
                         chain_id: self.runtime.chain_id(),
                         owner,
                     },
+                    relayer_fee: Amount::ZERO,
+                    deadline: Timestamp::from(0),
+                    nonce: 0,
                 },
                 signature_hex: String::new(),
             },
         };
-        self.runtime.call_application(true, token_app_id, &op);
+        self.runtime.call_application(true, asset, &op);
     }
 
-    async fn current_price(&self, symbol: &str) -> Amount {
+    fn wlin_app(&self) -> IntentsResult<ApplicationId<FungibleTokenAbi>> {
+        self.state
+            .wlin_app_id
+            .get()
+            .as_ref()
+            .map(|id| id.with_abi::<FungibleTokenAbi>())
+            .ok_or(IntentsError::WlinAppNotSet)
+    }
+
+    async fn token_app(&self, symbol: &str) -> IntentsResult<ApplicationId<FungibleTokenAbi>> {
+        self.state
+            .token_app_id_by_symbol
+            .get(symbol)
+            .await
+            .expect("Failed to read token app id")
+            .map(|id| id.with_abi::<FungibleTokenAbi>())
+            .ok_or(IntentsError::TokenAppNotFound)
+    }
+
+    async fn current_price(&self, symbol: &str) -> IntentsResult<Price> {
         let config = self
             .state
             .pools
             .get(symbol)
             .await
             .expect("Failed to read pool config")
-            .expect("Pool not found");
+            .ok_or(IntentsError::PoolNotFound)?;
         let x = self
             .state
             .wlin_reserves
             .get(symbol)
             .await
             .expect("Failed to read wLin reserve")
-            .unwrap_or_default()
-            .to_attos();
+            .unwrap_or_default();
         let y = self
             .state
             .token_reserves
             .get(symbol)
             .await
             .expect("Failed to read token reserve")
-            .unwrap_or_default()
-            .to_attos();
-        let v_x = config.v_x.to_attos();
-        let v_y = config.v_y.to_attos();
-        let price_attos = (x.saturating_add(v_x))
-            .saturating_mul(Amount::ONE.to_attos())
-            .saturating_div(y.saturating_add(v_y));
-        Amount::from_attos(price_attos)
-    }
-
-    fn verify_signature<T: std::fmt::Debug>(&self, payload: &T, signature_hex: &str) -> AccountOwner
-    where
-        for<'de> T: linera_base::crypto::BcsSignable<'de>,
-    {
-        let bytes = hex::decode(signature_hex).expect("Invalid signature hex");
-        let signature = AccountSignature::try_from(bytes.as_slice())
-            .expect("Invalid account signature");
-        signature.verify(payload).expect("Signature verification failed");
-        match signature {
-            AccountSignature::Ed25519 { public_key, .. } => AccountOwner::from(public_key),
-            AccountSignature::Secp256k1 { public_key, .. } => AccountOwner::from(public_key),
-            AccountSignature::EvmSecp256k1 { address, .. } => AccountOwner::Address20(address),
-        }
+            .unwrap_or_default();
+        Ok(spot_price(&config, x, y))
     }
 }
@@ -7,8 +7,11 @@ pub mod state;
 use linera_sdk::{graphql::GraphQLMutationRoot, linera_base_types::{ContractAbi, ServiceAbi}};
 use serde::{Deserialize, Serialize};
 
-use shared_types::{IntentId, PoolConfig, SignedIntent, SignedTradeRequest};
-use linera_sdk::linera_base_types::Amount;
+use shared_types::{
+    DevBuy, IntentId, IntentsError, IntentsResult, LaunchProtection, PoolConfig, Price, SignedClaimCreatorFeesRequest, SignedIntent, SignedTradeRequest,
+    SignedWithdrawIntentRequest, TradeReceipt,
+};
+use linera_sdk::linera_base_types::{AccountOwner, Amount};
 
 /// ABI definition for the matching-engine app.
 pub struct MatchingEngineAbi;
@@ -16,12 +19,23 @@ pub struct MatchingEngineAbi;
 /// Operations executed by the matching-engine app.
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
-    /// Operator sets the wLin app id for pricing/trades.
+    /// Admin sets the wLin app id for pricing/trades.
     SetWlinApp { app_id: String },
-    /// Operator sets fee destination for trade fees.
-    SetFeeDestination { owner: linera_sdk::linera_base_types::AccountOwner },
-    /// Create a pool for a token app (invoked by token factory).
-    CreatePool { symbol: String, token_app_id: String, config: PoolConfig },
+    /// Admin sets the destination for withdrawn protocol fees.
+    SetFeeDestination { owner: AccountOwner },
+    /// Admin withdraws accrued protocol fees (all of them when `amount` is
+    /// omitted) to the fee destination, or to the admin if none is set.
+    WithdrawFees { amount: Option<Amount> },
+    /// Admin registers the token-factory app allowed to request dev buys.
+    SetTokenFactory { app_id: String },
+    /// Create a pool for a token app; only the registered token factory may call it.
+    CreatePool {
+        symbol: String,
+        token_app_id: String,
+        config: PoolConfig,
+        creator: AccountOwner,
+        dev_buy: Option<DevBuy>,
+    },
     /// Direct trades.
     Buy { trade: SignedTradeRequest },
     Sell { trade: SignedTradeRequest },
@@ -29,26 +43,330 @@ pub enum Operation {
     PlaceIntent { intent: SignedIntent },
     /// Operator settles an intent (partial fill supported).
     SettleIntent { intent_id: IntentId, fill_amount: Amount },
+    /// Token creator withdraws their accrued share of trade fees.
+    ClaimCreatorFees { request: SignedClaimCreatorFeesRequest },
+    /// Admin halts or resumes trading on every pool.
+    SetPaused { paused: bool },
+    /// Admin halts or resumes trading on one pool (also clears a tripped
+    /// circuit breaker).
+    SetPoolPaused { symbol: String, paused: bool },
+    /// Admin sets the max spot-price move per block, in basis points, before
+    /// a pool is halted automatically (0 disables the breaker).
+    SetCircuitBreaker { max_move_bps: u16 },
+    /// Intent owner cancels an intent and recovers its escrow while trading
+    /// on its pool is paused.
+    WithdrawIntent { request: SignedWithdrawIntentRequest },
+    /// Token factory moves the creator fee share to a new creator.
+    UpdateCreator { symbol: String, creator: AccountOwner },
+    /// Admin replaces the launch protections of an existing pool.
+    SetLaunchProtection { symbol: String, protection: LaunchProtection },
+}
+
+/// Typed result of a matching-engine operation.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum MatchingEngineResponse {
+    /// Configuration operations that produce no data.
+    Ok,
+    /// A pool was created; `dev_buy` holds the creator's first trade, if any.
+    PoolCreated { dev_buy: Option<TradeReceipt> },
+    /// A direct buy or sell was executed.
+    Trade(TradeReceipt),
+    /// An intent was escrowed and recorded under `id`.
+    IntentPlaced { id: IntentId },
+    /// An intent was settled (fully or partially); `receipt` is `None` when
+    /// there was nothing left to fill.
+    IntentSettled {
+        id: IntentId,
+        receipt: Option<TradeReceipt>,
+        remaining: Amount,
+    },
+    /// Accrued creator fees were paid out in wLin.
+    CreatorFeesClaimed { symbol: String, amount: Amount },
+    /// Accrued protocol fees were paid out in wLin.
+    FeesWithdrawn { to: AccountOwner, amount: Amount },
+    /// An intent was cancelled and its escrow returned to the owner.
+    IntentWithdrawn { id: IntentId, amount: Amount },
 }
 
 /// Fixed bonding-curve parameters from `docs/intro.md`.
 pub fn fixed_pool_config() -> PoolConfig {
     PoolConfig {
         total_curve_supply: Amount::from_tokens(800_000_000),
-        initial_price: "0.0001".to_string(),
+        initial_price: "0.0001".parse().expect("valid price literal"),
         graduation_base_reserve: Amount::from_tokens(100_000),
         fee_bps: 100,
+        creator_fee_bps: 30,
         v_x: Amount::from_tokens(80_000),
         v_y: Amount::ZERO,
+        // A full 1,500 wLin launch buy moves the price ~374 bps, inside the
+        // 500 bps impact cap.
+        launch_protection: LaunchProtection {
+            trading_delay_secs: 0,
+            protection_window_secs: 300,
+            max_buy_per_owner: Amount::from_tokens(1_500),
+            max_price_impact_bps: 500,
+        },
     }
 }
 
+/// Spot price of a pool in wLin per token, including virtual reserves.
+pub fn spot_price(config: &PoolConfig, wlin_reserve: Amount, token_reserve: Amount) -> Price {
+    let (x, y) = curve_reserves(config, wlin_reserve, token_reserve);
+    Price::from_raw(mul_div(x, Price::ONE_RAW, y))
+}
+
+/// Tokens the curve releases for `dx` wLin entering it (after fees).
+pub fn buy_output(
+    config: &PoolConfig,
+    wlin_reserve: Amount,
+    token_reserve: Amount,
+    dx: Amount,
+) -> Amount {
+    let (x, y) = curve_reserves(config, wlin_reserve, token_reserve);
+    let new_token = mul_div(x, y, x.saturating_add(dx.to_attos()))
+        .saturating_sub(config.v_y.to_attos());
+    Amount::from_attos(token_reserve.to_attos().saturating_sub(new_token))
+}
+
+/// wLin the curve releases for `dy` tokens entering it (before fees).
+pub fn sell_output(
+    config: &PoolConfig,
+    wlin_reserve: Amount,
+    token_reserve: Amount,
+    dy: Amount,
+) -> Amount {
+    let (x, y) = curve_reserves(config, wlin_reserve, token_reserve);
+    let new_wlin = mul_div(x, y, y.saturating_add(dy.to_attos()))
+        .saturating_sub(config.v_x.to_attos());
+    Amount::from_attos(wlin_reserve.to_attos().saturating_sub(new_wlin))
+}
+
+/// Spot-price move from `from` to `to` in basis points of `from`, either way.
+pub fn price_move_bps(from: Price, to: Price) -> u128 {
+    if from.is_zero() {
+        return 0;
+    }
+    mul_div(from.raw().abs_diff(to.raw()), 10_000, from.raw())
+}
+
+/// True when the move from `reference` to `current` exceeds `max_move_bps`
+/// (0 disables the breaker).
+pub fn circuit_breaker_tripped(max_move_bps: u16, reference: Price, current: Price) -> bool {
+    max_move_bps != 0 && price_move_bps(reference, current) > u128::from(max_move_bps)
+}
+
+/// Splits a trade fee into `(creator share, protocol share)`.
+///
+/// The creator gets `creator_fee_bps / fee_bps` of the fee, rounded down; the
+/// protocol keeps the rest.
+pub fn split_fee(config: &PoolConfig, fee: Amount) -> (Amount, Amount) {
+    if config.fee_bps == 0 {
+        return (Amount::ZERO, fee);
+    }
+    let creator_fee = Amount::from_attos(mul_div(
+        fee.to_attos(),
+        u128::from(config.creator_fee_bps),
+        u128::from(config.fee_bps),
+    ));
+    (creator_fee, fee.saturating_sub(creator_fee))
+}
+
+/// Amount paid out by `WithdrawFees`: `requested`, or everything accrued when
+/// omitted.
+pub fn fee_withdrawal(accrued: Amount, requested: Option<Amount>) -> IntentsResult<Amount> {
+    let amount = requested.unwrap_or(accrued);
+    if amount > accrued {
+        return Err(IntentsError::AmountExceedsAvailable);
+    }
+    Ok(amount)
+}
+
+/// Real plus virtual reserves `(x, y)` in attos.
+fn curve_reserves(config: &PoolConfig, wlin_reserve: Amount, token_reserve: Amount) -> (u128, u128) {
+    (
+        wlin_reserve.to_attos().saturating_add(config.v_x.to_attos()),
+        token_reserve.to_attos().saturating_add(config.v_y.to_attos()),
+    )
+}
+
+/// `a * b / c` rounded down, with a 256-bit intermediate product.
+///
+/// Reserves are around 1e23 to 1e27 attos, so their products overflow `u128`.
+/// Saturates at `u128::MAX` when the quotient does not fit or `c` is zero.
+pub fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & LOW);
+    let (b_hi, b_lo) = (b >> 64, b & LOW);
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let mid = (lo_lo >> 64) + (hi_lo & LOW) + (lo_hi & LOW);
+    let lo = (lo_lo & LOW) | (mid << 64);
+    let hi = a_hi * b_hi + (hi_lo >> 64) + (lo_hi >> 64) + (mid >> 64);
+    if hi >= c {
+        return u128::MAX;
+    }
+    // Long division of `hi:lo` by `c`; the remainder always stays below `c`.
+    let (mut remainder, mut quotient) = (hi, 0u128);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
 impl ContractAbi for MatchingEngineAbi {
     type Operation = Operation;
-    type Response = ();
+    type Response = MatchingEngineResponse;
 }
 
 impl ServiceAbi for MatchingEngineAbi {
     type Query = async_graphql::Request;
     type QueryResponse = async_graphql::Response;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(value: u128) -> Amount {
+        Amount::from_tokens(value)
+    }
+
+    #[test]
+    fn mul_div_uses_a_wide_intermediate() {
+        assert_eq!(mul_div(6, 7, 4), 10);
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, u128::MAX - 1, u128::MAX), u128::MAX - 1);
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90), 1 << 110);
+    }
+
+    #[test]
+    fn mul_div_saturates_on_overflow() {
+        assert_eq!(mul_div(u128::MAX, 2, 1), u128::MAX);
+        assert_eq!(mul_div(1, 1, 0), u128::MAX);
+    }
+
+    #[test]
+    fn spot_price_matches_initial_price_at_defaults() {
+        let config = fixed_pool_config();
+        let price = spot_price(&config, Amount::ZERO, config.total_curve_supply);
+        assert_eq!(price, config.initial_price);
+    }
+
+    #[test]
+    fn spot_price_after_large_buy() {
+        let config = fixed_pool_config();
+        // 100_000 * 640M = 80_000 * 800M keeps the curve invariant.
+        let token_out =
+            buy_output(&config, Amount::ZERO, config.total_curve_supply, tokens(20_000));
+        assert_eq!(token_out, tokens(160_000_000));
+        let price = spot_price(&config, tokens(20_000), tokens(640_000_000));
+        assert_eq!(price, "0.00015625".parse().unwrap());
+    }
+
+    /// Spot price at `fixed_pool_config()` after a first buy of `gross` wLin.
+    fn price_after_first_buy(gross: Amount) -> Price {
+        let config = fixed_pool_config();
+        let fee = mul_div(gross.to_attos(), u128::from(config.fee_bps), 10_000);
+        let dx = gross.saturating_sub(Amount::from_attos(fee));
+        let token_out = buy_output(&config, Amount::ZERO, config.total_curve_supply, dx);
+        spot_price(&config, dx, config.total_curve_supply.saturating_sub(token_out))
+    }
+
+    #[test]
+    fn price_move_is_measured_on_real_prices() {
+        // A 2,000 wLin buy lifts the price from 0.0001 to ~0.000105011.
+        let before = fixed_pool_config().initial_price;
+        assert_eq!(price_move_bps(before, price_after_first_buy(tokens(2_000))), 501);
+        assert_eq!(price_move_bps(before, price_after_first_buy(tokens(1_500))), 374);
+        assert_eq!(price_move_bps(Price::ZERO, before), 0);
+    }
+
+    #[test]
+    fn circuit_breaker_trips_above_threshold() {
+        let before = fixed_pool_config().initial_price;
+        let after = price_after_first_buy(tokens(2_000));
+        assert!(circuit_breaker_tripped(500, before, after));
+        // Moves down count as well, relative to the higher reference (477 bps).
+        assert!(circuit_breaker_tripped(450, after, before));
+        assert!(!circuit_breaker_tripped(500, after, before));
+    }
+
+    #[test]
+    fn circuit_breaker_holds_at_threshold() {
+        let before = fixed_pool_config().initial_price;
+        let after = price_after_first_buy(tokens(2_000));
+        assert!(!circuit_breaker_tripped(501, before, after));
+        assert!(!circuit_breaker_tripped(0, before, after));
+        let one = Price::from_raw(Price::ONE_RAW);
+        let plus_five_percent = Price::from_raw(Price::ONE_RAW / 20 * 21);
+        assert!(!circuit_breaker_tripped(500, one, plus_five_percent));
+        assert!(circuit_breaker_tripped(499, one, plus_five_percent));
+    }
+
+    #[test]
+    fn default_launch_max_buy_fits_impact_cap() {
+        let protection = fixed_pool_config().launch_protection;
+        let before = fixed_pool_config().initial_price;
+        let max_buy = protection.max_buy_per_owner;
+        let impact_bps = price_move_bps(before, price_after_first_buy(max_buy));
+        assert_eq!(protection.check_buy(Amount::ZERO, max_buy, impact_bps), Ok(max_buy));
+        let impact_bps = price_move_bps(before, price_after_first_buy(tokens(2_000)));
+        assert_eq!(
+            protection.check_buy(Amount::ZERO, Amount::ZERO, impact_bps),
+            Err(IntentsError::PriceImpactTooHigh)
+        );
+    }
+
+    #[test]
+    fn split_fee_gives_creator_their_share() {
+        let config = fixed_pool_config();
+        assert_eq!(split_fee(&config, tokens(100)), (tokens(30), tokens(70)));
+        assert_eq!(split_fee(&config, Amount::ZERO), (Amount::ZERO, Amount::ZERO));
+        // Rounding favours the protocol.
+        assert_eq!(
+            split_fee(&config, Amount::from_attos(3)),
+            (Amount::ZERO, Amount::from_attos(3))
+        );
+    }
+
+    #[test]
+    fn split_fee_handles_edge_configs() {
+        let all_creator = PoolConfig { creator_fee_bps: 100, ..fixed_pool_config() };
+        assert_eq!(split_fee(&all_creator, tokens(5)), (tokens(5), Amount::ZERO));
+        let no_creator = PoolConfig { creator_fee_bps: 0, ..fixed_pool_config() };
+        assert_eq!(split_fee(&no_creator, tokens(5)), (Amount::ZERO, tokens(5)));
+        let no_fee = PoolConfig { fee_bps: 0, creator_fee_bps: 0, ..fixed_pool_config() };
+        assert_eq!(split_fee(&no_fee, tokens(5)), (Amount::ZERO, tokens(5)));
+    }
+
+    #[test]
+    fn fee_withdrawal_defaults_to_everything_accrued() {
+        assert_eq!(fee_withdrawal(tokens(70), None), Ok(tokens(70)));
+        assert_eq!(fee_withdrawal(tokens(70), Some(tokens(20))), Ok(tokens(20)));
+        assert_eq!(fee_withdrawal(tokens(70), Some(tokens(70))), Ok(tokens(70)));
+        assert_eq!(fee_withdrawal(Amount::ZERO, None), Ok(Amount::ZERO));
+    }
+
+    #[test]
+    fn fee_withdrawal_rejects_more_than_accrued() {
+        assert_eq!(
+            fee_withdrawal(tokens(70), Some(tokens(71))),
+            Err(IntentsError::AmountExceedsAvailable)
+        );
+    }
+
+    #[test]
+    fn sell_output_reverses_buy_output() {
+        let config = fixed_pool_config();
+        let wlin_out =
+            sell_output(&config, tokens(20_000), tokens(640_000_000), tokens(160_000_000));
+        assert_eq!(wlin_out, tokens(20_000));
+    }
+}
@@ -14,6 +14,7 @@ use linera_sdk::{
 
 use matching_engine::{MatchingEngineAbi, Operation};
 use matching_engine::state::MatchingEngineState;
+use shared_types::normalize_symbol;
 
 #[derive(Clone)]
 pub struct MatchingEngineService {
@@ -53,11 +54,46 @@ impl Service for MatchingEngineService {
 
 #[Object]
 impl MatchingEngineService {
+    async fn token_factory_app_id(&self) -> Option<String> {
+        self.state.token_factory_app_id.get().as_ref().map(|id| id.to_string())
+    }
+
     async fn wlin_app_id(&self) -> Option<String> {
         self.state.wlin_app_id.get().as_ref().map(|id| id.to_string())
     }
 
+    async fn admin(&self) -> Option<linera_sdk::linera_base_types::AccountOwner> {
+        *self.state.admin.get()
+    }
+
+    async fn fee_destination(&self) -> Option<linera_sdk::linera_base_types::AccountOwner> {
+        *self.state.fee_destination.get()
+    }
+
+    async fn protocol_fees(&self) -> linera_sdk::linera_base_types::Amount {
+        *self.state.protocol_fees.get()
+    }
+
+    async fn paused(&self) -> bool {
+        *self.state.paused.get()
+    }
+
+    async fn pool_paused(&self, symbol: String) -> bool {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .paused_symbols
+            .get(&symbol)
+            .await
+            .expect("Failed to read pool pause flag")
+            .unwrap_or(false)
+    }
+
+    async fn circuit_breaker_bps(&self) -> u16 {
+        *self.state.circuit_breaker_bps.get()
+    }
+
     async fn token_app_id(&self, symbol: String) -> Option<String> {
+        let symbol = normalize_symbol(&symbol);
         self.state
             .token_app_id_by_symbol
             .get(&symbol)
@@ -67,6 +103,7 @@ impl MatchingEngineService {
     }
 
     async fn pool_config(&self, symbol: String) -> Option<shared_types::PoolConfig> {
+        let symbol = normalize_symbol(&symbol);
         self.state
             .pools
             .get(&symbol)
@@ -75,6 +112,7 @@ impl MatchingEngineService {
     }
 
     async fn wlin_reserve(&self, symbol: String) -> Option<linera_sdk::linera_base_types::Amount> {
+        let symbol = normalize_symbol(&symbol);
         self.state
             .wlin_reserves
             .get(&symbol)
@@ -83,6 +121,7 @@ impl MatchingEngineService {
     }
 
     async fn token_reserve(&self, symbol: String) -> Option<linera_sdk::linera_base_types::Amount> {
+        let symbol = normalize_symbol(&symbol);
         self.state
             .token_reserves
             .get(&symbol)
@@ -90,7 +129,84 @@ impl MatchingEngineService {
             .expect("Failed to read token reserve")
     }
 
+    async fn spot_price(&self, symbol: String) -> Option<shared_types::Price> {
+        let symbol = normalize_symbol(&symbol);
+        let config = self
+            .state
+            .pools
+            .get(&symbol)
+            .await
+            .expect("Failed to read pool config")?;
+        let wlin = self
+            .state
+            .wlin_reserves
+            .get(&symbol)
+            .await
+            .expect("Failed to read wLin reserve")
+            .unwrap_or_default();
+        let token = self
+            .state
+            .token_reserves
+            .get(&symbol)
+            .await
+            .expect("Failed to read token reserve")
+            .unwrap_or_default();
+        Some(matching_engine::spot_price(&config, wlin, token))
+    }
+
+    async fn creator_fees(&self, symbol: String) -> Option<linera_sdk::linera_base_types::Amount> {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .creator_fees
+            .get(&symbol)
+            .await
+            .expect("Failed to read creator fees")
+    }
+
+    async fn pool_creator(&self, symbol: String) -> Option<linera_sdk::linera_base_types::AccountOwner> {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .creator_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read creator")
+    }
+
+    async fn pool_created_at(&self, symbol: String) -> Option<linera_sdk::linera_base_types::Timestamp> {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .pool_created_at
+            .get(&symbol)
+            .await
+            .expect("Failed to read pool creation time")
+    }
+
+    async fn launch_bought(
+        &self,
+        symbol: String,
+        owner: linera_sdk::linera_base_types::AccountOwner,
+    ) -> linera_sdk::linera_base_types::Amount {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .launch_bought
+            .get(&(symbol, owner))
+            .await
+            .expect("Failed to read launch purchases")
+            .unwrap_or_default()
+    }
+
+    /// Nonce the owner's next explicitly signed trade or intent must carry.
+    async fn trade_nonce(&self, owner: linera_sdk::linera_base_types::AccountOwner) -> u64 {
+        self.state
+            .trade_nonces
+            .get(&owner)
+            .await
+            .expect("Failed to read trade nonce")
+            .unwrap_or_default()
+    }
+
     async fn lp_enabled(&self, symbol: String) -> Option<bool> {
+        let symbol = normalize_symbol(&symbol);
         self.state
             .lp_enabled
             .get(&symbol)
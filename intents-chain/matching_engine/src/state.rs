@@ -5,19 +5,25 @@
 //! `ContractRuntime::call_application`.
 
 use linera_base::identifiers::ApplicationId;
-use linera_sdk::linera_base_types::{AccountOwner, Amount};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, BlockHeight, Timestamp};
 use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
 
-use shared_types::{Intent, IntentId, PoolConfig};
+use shared_types::{Intent, IntentId, PoolConfig, Price};
 
 /// Persistent storage for the matching-engine app.
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct MatchingEngineState {
+    /// Admin allowed to change fee settings (signer at instantiation).
+    pub admin: RegisterView<Option<AccountOwner>>,
+    /// Token-factory app id trusted to request creator dev buys.
+    pub token_factory_app_id: RegisterView<Option<ApplicationId>>,
     /// wLin application id used as the pricing asset.
     pub wlin_app_id: RegisterView<Option<ApplicationId>>,
-    /// Fee destination for curve fees (operator).
+    /// Fee destination for withdrawn protocol fees (operator).
     pub fee_destination: RegisterView<Option<AccountOwner>>,
+    /// Accrued, unwithdrawn protocol fees in wLin (held in app custody).
+    pub protocol_fees: RegisterView<Amount>,
 
     /// Token app id per symbol (each token is its own app instance).
     pub token_app_id_by_symbol: MapView<String, ApplicationId>,
@@ -31,17 +37,45 @@ pub struct MatchingEngineState {
 
     /// Stored user intents (limit orders).
     pub intents: MapView<IntentId, Intent>,
-    /// Intent status (NotFilled / PartiallyFilled / Filled).
+    /// Intent status (NotFilled / PartiallyFilled / Filled / Cancelled).
     pub intent_status: MapView<IntentId, IntentStatus>,
     /// Remaining unfilled amount per intent.
     pub intent_remaining: MapView<IntentId, Amount>,
     /// Escrowed amount per intent (reserved funds/tokens).
     pub intent_escrowed: MapView<IntentId, Amount>,
-    /// Next intent id per symbol (monotonic counter).
-    pub next_intent_id: MapView<String, u64>,
+    /// Last issued intent id (global monotonic counter shared by all symbols).
+    pub next_intent_id: RegisterView<u64>,
+    /// Next expected nonce of explicitly signed trades and intents per owner.
+    pub trade_nonces: MapView<AccountOwner, u64>,
+
+    /// Token creator per symbol (receives the creator fee share).
+    pub creator_by_symbol: MapView<String, AccountOwner>,
+    /// Accrued, unclaimed creator fees in wLin per symbol.
+    pub creator_fees: MapView<String, Amount>,
 
     /// Graduation flag per token symbol (LPs enabled after reserve threshold).
     pub lp_enabled: MapView<String, bool>,
+
+    /// Global trading halt (admin).
+    pub paused: RegisterView<bool>,
+    /// Per-symbol trading halt (admin or circuit breaker).
+    pub paused_symbols: MapView<String, bool>,
+    /// Max spot-price move per block in basis points (0 = disabled).
+    pub circuit_breaker_bps: RegisterView<u16>,
+    /// Spot price at the start of the current block per symbol.
+    pub block_reference_price: MapView<String, PriceReference>,
+
+    /// Pool creation time per symbol (start of launch protection).
+    pub pool_created_at: MapView<String, Timestamp>,
+    /// wLin spent on buys per (symbol, owner) during the launch window.
+    pub launch_bought: MapView<(String, AccountOwner), Amount>,
+}
+
+/// Spot price observed before the first trade of a block.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PriceReference {
+    pub height: BlockHeight,
+    pub price: Price,
 }
 
 /// Intent status for escrowed matching.
@@ -50,4 +84,6 @@ pub enum IntentStatus {
     NotFilled,
     PartiallyFilled,
     Filled,
+    /// Escrow returned to the owner before the intent was filled.
+    Cancelled,
 }
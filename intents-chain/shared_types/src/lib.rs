@@ -7,10 +7,45 @@
 //! - `intents-chain/src/service.rs` (queries)
 //! - `intents-chain/src/state.rs` (persistent storage)
 
+use std::fmt;
+use std::str::FromStr;
+
 use async_graphql::{Enum, InputObject, SimpleObject, scalar};
 use linera_base::crypto::BcsSignable;
-use linera_sdk::linera_base_types::{AccountOwner, Amount};
-use serde::{Deserialize, Serialize};
+use linera_sdk::linera_base_types::{AccountOwner, Amount, TimeDelta, Timestamp};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Shortest allowed token symbol.
+pub const MIN_SYMBOL_LEN: usize = 2;
+/// Longest allowed token symbol.
+pub const MAX_SYMBOL_LEN: usize = 10;
+/// Longest allowed token display name, in characters.
+pub const MAX_NAME_LEN: usize = 32;
+/// The only accepted `TokenMetadata::decimals`.
+///
+/// Balances, reserves and prices are all `Amount`s with 18 decimal places, so
+/// a token advertising any other precision would be displayed wrongly.
+pub const TOKEN_DECIMALS: u8 = 18;
+
+/// Canonical form of a token symbol (trimmed, upper-case).
+///
+/// All registries key by this form so "tst" and "TST" cannot coexist.
+pub fn normalize_symbol(symbol: &str) -> String {
+    symbol.trim().to_ascii_uppercase()
+}
+
+/// Normalizes `symbol` and checks its grammar: `MIN_SYMBOL_LEN..=MAX_SYMBOL_LEN`
+/// ASCII letters and digits, starting with a letter.
+pub fn validate_symbol(symbol: &str) -> IntentsResult<String> {
+    let symbol = normalize_symbol(symbol);
+    let valid_len = (MIN_SYMBOL_LEN..=MAX_SYMBOL_LEN).contains(&symbol.len());
+    let valid_chars = symbol.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+    let starts_with_letter = symbol.bytes().next().is_some_and(|b| b.is_ascii_uppercase());
+    if !(valid_len && valid_chars && starts_with_letter) {
+        return Err(IntentsError::InvalidSymbol);
+    }
+    Ok(symbol)
+}
 
 /// Token metadata stored on-chain.
 ///
@@ -22,10 +57,141 @@ pub struct TokenMetadata {
     pub name: String,
     /// Token symbol (unique).
     pub symbol: String,
-    /// Token decimals (implicit; no normalization enforced in code).
+    /// Token decimals; always `TOKEN_DECIMALS`.
     pub decimals: u8,
 }
 
+impl TokenMetadata {
+    /// Validates name and decimals and normalizes the symbol in place.
+    pub fn validate(&mut self) -> IntentsResult<()> {
+        self.symbol = validate_symbol(&self.symbol)?;
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(IntentsError::InvalidName);
+        }
+        self.name = name.to_string();
+        if self.decimals != TOKEN_DECIMALS {
+            return Err(IntentsError::InvalidDecimals);
+        }
+        Ok(())
+    }
+}
+
+/// Longest allowed `TokenProfile::description`, in bytes.
+pub const MAX_DESCRIPTION_LEN: usize = 1_000;
+/// Longest allowed URI or handle in `TokenProfile`, in bytes.
+pub const MAX_LINK_LEN: usize = 256;
+
+/// Creator-editable presentation data shown on the explorer page.
+///
+/// Stored separately from `TokenMetadata` so it can change after launch.
+/// Empty strings mean "not set".
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, InputObject, SimpleObject)]
+#[graphql(input_name = "TokenProfileInput")]
+pub struct TokenProfile {
+    /// Free-form project description.
+    pub description: String,
+    /// Logo or banner image URI (https or ipfs).
+    pub image_uri: String,
+    /// Project website.
+    pub website: String,
+    /// X / Twitter handle or URL.
+    pub twitter: String,
+    /// Telegram handle or URL.
+    pub telegram: String,
+    /// Discord invite URL.
+    pub discord: String,
+}
+
+impl TokenProfile {
+    /// Enforces the per-field size limits.
+    pub fn validate(&self) -> IntentsResult<()> {
+        let links = [&self.image_uri, &self.website, &self.twitter, &self.telegram, &self.discord];
+        if self.description.len() > MAX_DESCRIPTION_LEN
+            || links.iter().any(|link| link.len() > MAX_LINK_LEN)
+        {
+            return Err(IntentsError::ProfileTooLarge);
+        }
+        Ok(())
+    }
+}
+
+/// Token profile update (creator -> token factory).
+///
+/// Used by `Operation::UpdateTokenProfile` after signature verification.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct UpdateTokenProfileRequest {
+    /// Token creator / signer.
+    pub owner: AccountOwner,
+    /// Token symbol.
+    pub symbol: String,
+    /// Replacement profile.
+    pub profile: TokenProfile,
+    /// Must equal the symbol's current profile nonce (replay protection).
+    pub nonce: u64,
+}
+
+impl<'de> BcsSignable<'de> for UpdateTokenProfileRequest {}
+
+/// Signed token profile update with creator signature.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct SignedUpdateTokenProfileRequest {
+    /// Update payload.
+    pub payload: UpdateTokenProfileRequest,
+    /// Hex-encoded AccountSignature bytes.
+    pub signature_hex: String,
+}
+
+/// First step of a creator hand-over (current creator -> token factory).
+///
+/// Used by `Operation::TransferCreator` after signature verification.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct TransferCreatorRequest {
+    /// Current creator / signer.
+    pub owner: AccountOwner,
+    /// Token symbol.
+    pub symbol: String,
+    /// Proposed new creator, who must accept.
+    pub new_creator: AccountOwner,
+    /// Must equal the symbol's current creator nonce (replay protection).
+    pub nonce: u64,
+}
+
+impl<'de> BcsSignable<'de> for TransferCreatorRequest {}
+
+/// Signed creator hand-over with current creator signature.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct SignedTransferCreatorRequest {
+    /// Hand-over payload.
+    pub payload: TransferCreatorRequest,
+    /// Hex-encoded AccountSignature bytes.
+    pub signature_hex: String,
+}
+
+/// Second step of a creator hand-over (pending creator -> token factory).
+///
+/// Used by `Operation::AcceptCreator` after signature verification.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct AcceptCreatorRequest {
+    /// Pending creator / signer.
+    pub owner: AccountOwner,
+    /// Token symbol.
+    pub symbol: String,
+    /// Must equal the symbol's current creator nonce (replay protection).
+    pub nonce: u64,
+}
+
+impl<'de> BcsSignable<'de> for AcceptCreatorRequest {}
+
+/// Signed creator acceptance with pending creator signature.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct SignedAcceptCreatorRequest {
+    /// Acceptance payload.
+    pub payload: AcceptCreatorRequest,
+    /// Hex-encoded AccountSignature bytes.
+    pub signature_hex: String,
+}
+
 /// Token creation request (user -> token factory).
 ///
 /// Used by `Operation::CreateToken` after signature verification.
@@ -35,8 +201,73 @@ pub struct CreateTokenRequest {
     pub owner: AccountOwner,
     /// Metadata (symbol, name, decimals).
     pub metadata: TokenMetadata,
-    /// Initial supply to mint to the creator.
+    /// Total supply: the fixed curve supply plus all allocations.
     pub initial_supply: Amount,
+    /// Optional creator buy executed as the pool's first trade.
+    pub dev_buy: Option<DevBuy>,
+    /// Locked allocations minted on top of the curve supply.
+    #[graphql(default)]
+    pub allocations: Vec<Allocation>,
+}
+
+/// Most allocations a token may be created with.
+pub const MAX_ALLOCATIONS: usize = 10;
+
+/// Most recipients a single fungible `BatchTransfer` may credit.
+pub const MAX_BATCH_RECIPIENTS: usize = 100;
+
+/// Locked share of a new token's supply released to `beneficiary`.
+///
+/// Nothing is claimable before `cliff_secs` after creation; from then on the
+/// vested amount grows linearly until it is complete at `vesting_secs`.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject, SimpleObject)]
+#[graphql(input_name = "AllocationInput")]
+pub struct Allocation {
+    /// Owner allowed to claim the vested tokens.
+    pub beneficiary: AccountOwner,
+    /// Total tokens locked.
+    pub amount: Amount,
+    /// Seconds after creation before anything vests.
+    pub cliff_secs: u64,
+    /// Seconds after creation until everything has vested.
+    pub vesting_secs: u64,
+}
+
+impl Allocation {
+    /// Checks a set of allocations and returns their total.
+    pub fn validate_all(allocations: &[Allocation]) -> IntentsResult<Amount> {
+        if allocations.len() > MAX_ALLOCATIONS {
+            return Err(IntentsError::InvalidAllocation);
+        }
+        let mut total = Amount::ZERO;
+        for (index, allocation) in allocations.iter().enumerate() {
+            let duplicate = allocations[..index]
+                .iter()
+                .any(|other| other.beneficiary == allocation.beneficiary);
+            if allocation.amount == Amount::ZERO
+                || allocation.cliff_secs > allocation.vesting_secs
+                || duplicate
+            {
+                return Err(IntentsError::InvalidAllocation);
+            }
+            total = total
+                .try_add(allocation.amount)
+                .map_err(|_| IntentsError::InvalidAllocation)?;
+        }
+        Ok(total)
+    }
+}
+
+/// Creator buy executed atomically with token creation.
+///
+/// Paid from the creator's wLin allowance to the matching engine and exempt
+/// from launch protections.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct DevBuy {
+    /// wLin to spend.
+    pub amount: Amount,
+    /// Minimum tokens to receive (slippage protection).
+    pub min_out: Amount,
 }
 
 impl<'de> BcsSignable<'de> for CreateTokenRequest {}
@@ -49,20 +280,173 @@ impl<'de> BcsSignable<'de> for CreateTokenRequest {}
 pub struct PoolConfig {
     /// Total curve supply in the pool (token reserve).
     pub total_curve_supply: Amount,
-    /// Initial price string (human-friendly).
-    pub initial_price: String,
+    /// Initial spot price in wLin per token.
+    pub initial_price: Price,
     /// Graduation reserve threshold in wLin.
     pub graduation_base_reserve: Amount,
     /// Fee in basis points charged on trades.
     pub fee_bps: u16,
+    /// Portion of `fee_bps` accrued to the token creator; the rest goes to
+    /// the protocol.
+    pub creator_fee_bps: u16,
     /// Virtual reserve X (wLin) for curve shape.
     pub v_x: Amount,
     /// Virtual reserve Y (token) for curve shape.
     pub v_y: Amount,
+    /// Anti-sniping limits applied to buys right after launch.
+    pub launch_protection: LaunchProtection,
+}
+
+/// Anti-sniping limits for a newly created pool.
+///
+/// Trading opens `trading_delay_secs` after pool creation; during the following
+/// `protection_window_secs` every buy is subject to the per-owner and
+/// price-impact limits. Zero disables the corresponding limit.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, InputObject, SimpleObject, PartialEq, Eq)]
+#[graphql(input_name = "LaunchProtectionInput")]
+pub struct LaunchProtection {
+    /// Delay between pool creation and the first allowed buy, in seconds.
+    pub trading_delay_secs: u64,
+    /// Length of the protected window once trading opens, in seconds.
+    pub protection_window_secs: u64,
+    /// Max cumulative wLin an owner may spend on buys during the window.
+    pub max_buy_per_owner: Amount,
+    /// Max spot-price increase caused by a single buy, in basis points.
+    pub max_price_impact_bps: u16,
+}
+
+impl LaunchProtection {
+    /// True while a buy at `now` on a pool created at `created_at` falls in the
+    /// protected window; fails before trading opens.
+    pub fn is_protected(&self, created_at: Timestamp, now: Timestamp) -> IntentsResult<bool> {
+        let opens_at = created_at.saturating_add(TimeDelta::from_secs(self.trading_delay_secs));
+        if now < opens_at {
+            return Err(IntentsError::TradingNotStarted);
+        }
+        let protected_until =
+            opens_at.saturating_add(TimeDelta::from_secs(self.protection_window_secs));
+        Ok(now < protected_until)
+    }
+
+    /// Checks a protected buy of `amount_in` wLin that moves the spot price by
+    /// `impact_bps`, from an owner who already spent `bought` in the window.
+    /// Returns the owner's new window total.
+    pub fn check_buy(
+        &self,
+        bought: Amount,
+        amount_in: Amount,
+        impact_bps: u128,
+    ) -> IntentsResult<Amount> {
+        if self.max_price_impact_bps > 0 && impact_bps > u128::from(self.max_price_impact_bps) {
+            return Err(IntentsError::PriceImpactTooHigh);
+        }
+        let bought = bought.saturating_add(amount_in);
+        if self.max_buy_per_owner > Amount::ZERO && bought > self.max_buy_per_owner {
+            return Err(IntentsError::LaunchBuyLimitExceeded);
+        }
+        Ok(bought)
+    }
 }
 
 // (CreatePoolRequest removed; pool creation is an app-level operation in matching_engine.)
 
+/// Fixed-point price in wLin per token with `Price::DECIMALS` fractional digits.
+///
+/// Exposed to GraphQL as a decimal string (e.g. `"0.0001"`) that is validated
+/// when the request is parsed; BCS encodes the raw `u128` so signatures cover
+/// the exact value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Price(u128);
+
+impl Price {
+    /// Number of fractional decimal digits.
+    pub const DECIMALS: u8 = 18;
+    /// Zero price.
+    pub const ZERO: Price = Price(0);
+    /// Raw units per 1.0 (matches `Amount` attos).
+    pub const ONE_RAW: u128 = 1_000_000_000_000_000_000;
+
+    /// Builds a price from raw fixed-point units.
+    pub const fn from_raw(raw: u128) -> Self {
+        Price(raw)
+    }
+
+    /// Raw fixed-point units.
+    pub const fn raw(self) -> u128 {
+        self.0
+    }
+
+    /// True when the price is zero.
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl FromStr for Price {
+    type Err = IntentsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (int_part, frac_part) = match s.split_once('.') {
+            Some((int_part, frac_part)) => (int_part, frac_part),
+            None => (s, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if int_part.is_empty()
+            || !is_digits(int_part)
+            || !is_digits(frac_part)
+            || frac_part.len() > Price::DECIMALS as usize
+        {
+            return Err(IntentsError::InvalidPrice);
+        }
+        let int_value: u128 = int_part.parse().map_err(|_| IntentsError::InvalidPrice)?;
+        let mut frac_value: u128 = 0;
+        for digit in frac_part.bytes() {
+            frac_value = frac_value * 10 + u128::from(digit - b'0');
+        }
+        frac_value *= 10u128.pow((Price::DECIMALS as usize - frac_part.len()) as u32);
+        int_value
+            .checked_mul(Price::ONE_RAW)
+            .and_then(|raw| raw.checked_add(frac_value))
+            .map(Price)
+            .ok_or(IntentsError::InvalidPrice)
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let int_value = self.0 / Price::ONE_RAW;
+        let frac_value = self.0 % Price::ONE_RAW;
+        if frac_value == 0 {
+            return write!(f, "{int_value}");
+        }
+        let frac = format!("{:0width$}", frac_value, width = Price::DECIMALS as usize);
+        write!(f, "{int_value}.{}", frac.trim_end_matches('0'))
+    }
+}
+
+impl Serialize for Price {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_string())
+        } else {
+            serializer.serialize_u128(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.parse().map_err(serde::de::Error::custom)
+        } else {
+            u128::deserialize(deserializer).map(Price)
+        }
+    }
+}
+
+scalar!(Price);
+
 /// Trade side for buys/sells.
 #[derive(Clone, Debug, Deserialize, Serialize, Enum, Copy, Eq, PartialEq)]
 pub enum Side {
@@ -87,10 +471,64 @@ pub struct TradeRequest {
     pub amount: Amount,
     /// Minimum output required (slippage protection).
     pub min_out: Amount,
+    /// wLin tip paid to the block signer relaying this request; skipped when
+    /// the owner signs the block.
+    #[graphql(default)]
+    pub relayer_fee: Amount,
+    /// Last moment a signed request may execute (unchecked without a signature).
+    #[graphql(default)]
+    pub deadline: Timestamp,
+    /// Owner's current trade nonce (unchecked without a signature).
+    #[graphql(default)]
+    pub nonce: u64,
 }
 
 impl<'de> BcsSignable<'de> for TradeRequest {}
 
+/// Creator fee claim (token creator -> matching engine).
+///
+/// Used by `Operation::ClaimCreatorFees` after signature verification.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct ClaimCreatorFeesRequest {
+    /// Token creator claiming the fees.
+    pub owner: AccountOwner,
+    /// Token symbol whose accrued fees are claimed.
+    pub symbol: String,
+}
+
+impl<'de> BcsSignable<'de> for ClaimCreatorFeesRequest {}
+
+/// Signed creator fee claim with user signature.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct SignedClaimCreatorFeesRequest {
+    /// Claim payload.
+    pub payload: ClaimCreatorFeesRequest,
+    /// Hex-encoded AccountSignature bytes.
+    pub signature_hex: String,
+}
+
+/// Receipt for a trade executed against a bonding-curve pool.
+///
+/// Returned by `Operation::Buy`, `Operation::Sell` and intent settlement so
+/// callers learn the fill without re-querying reserves.
+#[derive(Clone, Debug, Deserialize, Serialize, SimpleObject, PartialEq, Eq)]
+pub struct TradeReceipt {
+    /// Token symbol traded.
+    pub symbol: String,
+    /// Buy or sell.
+    pub side: Side,
+    /// Amount taken from the trader (wLin for Buy, token for Sell).
+    pub amount_in: Amount,
+    /// Amount delivered to the trader (token for Buy, wLin for Sell).
+    pub amount_out: Amount,
+    /// Fee charged in wLin.
+    pub fee: Amount,
+    /// Pool wLin reserve after the trade.
+    pub wlin_reserve: Amount,
+    /// Pool token reserve after the trade.
+    pub token_reserve: Amount,
+}
+
 /// Signed trade request with user signature.
 ///
 /// Signature is verified in contract before applying the trade.
@@ -100,6 +538,37 @@ pub struct SignedTradeRequest {
     pub payload: TradeRequest,
     /// Hex-encoded AccountSignature bytes.
     pub signature_hex: String,
+    /// Allowance for the input asset, applied before the trade pulls funds.
+    pub permit: Option<Permit>,
+}
+
+/// Signed allowance granting the matching engine access to the input asset.
+///
+/// Forwarded by the matching engine as a fungible `Operation::Permit` with the
+/// trade owner as owner and the matching engine as spender.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct Permit {
+    /// Allowance to set.
+    pub allowance: Amount,
+    /// Last moment the permit may be applied.
+    pub deadline: Timestamp,
+    /// Owner's current permit nonce on the asset app.
+    pub nonce: u64,
+    /// Hex-encoded signature over the fungible `PermitRequest`.
+    pub signature_hex: String,
+}
+
+impl Permit {
+    /// Fails unless the permit carries the owner's own signature.
+    ///
+    /// The asset app trusts the matching engine as a caller, so an unsigned
+    /// permit forwarded by it would set an allowance nobody signed.
+    pub fn ensure_signed(&self) -> IntentsResult<()> {
+        if self.signature_hex.is_empty() {
+            return Err(IntentsError::MissingSignature);
+        }
+        Ok(())
+    }
 }
 
 /// Intent request (limit order) recorded for off-chain matching.
@@ -115,8 +584,18 @@ pub struct Intent {
     pub side: Side,
     /// Amount to trade when settled.
     pub amount: Amount,
-    /// Limit price as string (parsed into Amount).
-    pub limit_price: String,
+    /// Limit price in wLin per token.
+    pub limit_price: Price,
+    /// wLin tip paid at placement to the block signer relaying this intent;
+    /// skipped when the owner signs the block.
+    #[graphql(default)]
+    pub relayer_fee: Amount,
+    /// Last moment a signed intent may be placed (unchecked without a signature).
+    #[graphql(default)]
+    pub deadline: Timestamp,
+    /// Owner's current trade nonce (unchecked without a signature).
+    #[graphql(default)]
+    pub nonce: u64,
 }
 
 impl<'de> BcsSignable<'de> for Intent {}
@@ -130,14 +609,38 @@ pub struct SignedIntent {
     pub payload: Intent,
     /// Hex-encoded AccountSignature bytes.
     pub signature_hex: String,
+    /// Allowance for the escrowed asset, applied before escrow.
+    pub permit: Option<Permit>,
 }
 
 /// Unique identifier for an intent.
 ///
-/// Generated per symbol by `next_intent_id`.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Generated by the matching engine's global `next_intent_id` counter.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct IntentId(pub u64);
 
+/// Escrow withdrawal for an intent whose pool is paused.
+///
+/// Used by `Operation::WithdrawIntent` after signature verification.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct WithdrawIntentRequest {
+    /// Intent owner / signer.
+    pub owner: AccountOwner,
+    /// Intent to cancel.
+    pub intent_id: IntentId,
+}
+
+impl<'de> BcsSignable<'de> for WithdrawIntentRequest {}
+
+/// Signed intent withdrawal with user signature.
+#[derive(Clone, Debug, Deserialize, Serialize, InputObject)]
+pub struct SignedWithdrawIntentRequest {
+    /// Withdrawal payload.
+    pub payload: WithdrawIntentRequest,
+    /// Hex-encoded AccountSignature bytes.
+    pub signature_hex: String,
+}
+
 /// Signed token creation request with user signature.
 ///
 /// Verified before minting initial supply and creating a pool.
@@ -150,3 +653,421 @@ pub struct SignedCreateTokenRequest {
 }
 
 scalar!(IntentId);
+
+/// Failure raised by the intents contracts.
+///
+/// Every variant has a stable `code()` that clients can map to UI messages.
+/// Contracts abort with the `Display` form (`"<CODE>: <message>"`), so the code
+/// is the first token of the block failure string.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum IntentsError {
+    /// `signature_hex` is not valid hex.
+    InvalidSignatureHex,
+    /// Signature bytes do not decode to an `AccountSignature`.
+    InvalidSignature,
+    /// Signature does not match the payload.
+    SignatureVerificationFailed,
+    /// No signature and no authenticated signer or trusted caller.
+    MissingSignature,
+    /// Recovered signer differs from the payload owner.
+    SignerMismatch,
+    /// Application id string could not be parsed.
+    InvalidApplicationId,
+    /// Module id string could not be parsed.
+    InvalidModuleId,
+    /// Owner string could not be parsed.
+    InvalidOwner,
+    /// wLin app id has not been configured.
+    WlinAppNotSet,
+    /// Fungible-token module id has not been configured.
+    ModuleNotSet,
+    /// Matching-engine app id has not been configured.
+    MatchingEngineNotSet,
+    /// A pool already exists for the symbol.
+    PoolAlreadyExists,
+    /// No pool exists for the symbol.
+    PoolNotFound,
+    /// No token app is registered for the symbol.
+    TokenAppNotFound,
+    /// A token with this symbol already exists.
+    SymbolAlreadyExists,
+    /// Initial supply does not match the curve supply plus allocations.
+    InvalidInitialSupply,
+    /// Amount must be greater than zero.
+    ZeroAmount,
+    /// Trade output is below `min_out`.
+    MinOutNotSatisfied,
+    /// No intent exists with the given id.
+    IntentNotFound,
+    /// Price is malformed, zero, or exceeds the supported precision.
+    InvalidPrice,
+    /// Current price does not satisfy the intent's limit price.
+    LimitPriceNotSatisfied,
+    /// Owner balance is too low.
+    InsufficientBalance,
+    /// Spender allowance is too low.
+    AllowanceExceeded,
+    /// Per-owner faucet cap for the current window would be exceeded.
+    FaucetCapExceeded,
+    /// Fee split is inconsistent (creator share above total fee).
+    InvalidFeeConfig,
+    /// Caller is not the creator of the token.
+    NotCreator,
+    /// Operation is restricted to the app admin.
+    Unauthorized,
+    /// Requested amount exceeds the available balance.
+    AmountExceedsAvailable,
+    /// Trading is paused globally or for this pool.
+    TradingPaused,
+    /// Escrow can only be withdrawn while trading is paused.
+    PoolNotPaused,
+    /// Caller does not own the intent.
+    NotIntentOwner,
+    /// Intent is already filled or withdrawn.
+    IntentClosed,
+    /// Pool has not opened for trading yet.
+    TradingNotStarted,
+    /// Buy exceeds the per-owner launch limit.
+    LaunchBuyLimitExceeded,
+    /// Buy moves the price more than the launch limit allows.
+    PriceImpactTooHigh,
+    /// Treasury account has not been configured.
+    TreasuryNotSet,
+    /// Symbol violates the symbol grammar.
+    InvalidSymbol,
+    /// Symbol is reserved by the admin.
+    ReservedSymbol,
+    /// Token name is empty or too long.
+    InvalidName,
+    /// Token decimals differ from `TOKEN_DECIMALS`.
+    InvalidDecimals,
+    /// A token profile field exceeds its size limit.
+    ProfileTooLarge,
+    /// Caller is not the pending creator of the token.
+    NotPendingCreator,
+    /// Permit deadline has passed.
+    PermitExpired,
+    /// Permit nonce does not match the owner's current nonce.
+    InvalidPermitNonce,
+    /// Operation requires a native-wrapping token.
+    NotWrappedNative,
+    /// Global faucet budget for the current window is spent.
+    FaucetBudgetExhausted,
+    /// Allocations are malformed, duplicated or too many.
+    InvalidAllocation,
+    /// No vested tokens are claimable yet.
+    NothingVested,
+    /// Batch transfer is empty, exceeds the recipient limit or overflows.
+    InvalidBatch,
+    /// Request nonce does not match the current nonce.
+    InvalidNonce,
+    /// Signed request deadline has passed.
+    RequestExpired,
+    /// Token cannot be minted (wrapped native or no minter configured).
+    MintDisabled,
+    /// Wrapped native tokens cannot leave the chain holding their backing.
+    WrappedNativeCrossChain,
+}
+
+/// Result alias used by contract helpers.
+pub type IntentsResult<T> = Result<T, IntentsError>;
+
+impl IntentsError {
+    /// Stable machine-readable code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            IntentsError::InvalidSignatureHex => "INVALID_SIGNATURE_HEX",
+            IntentsError::InvalidSignature => "INVALID_SIGNATURE",
+            IntentsError::SignatureVerificationFailed => "SIGNATURE_VERIFICATION_FAILED",
+            IntentsError::MissingSignature => "MISSING_SIGNATURE",
+            IntentsError::SignerMismatch => "SIGNER_MISMATCH",
+            IntentsError::InvalidApplicationId => "INVALID_APPLICATION_ID",
+            IntentsError::InvalidModuleId => "INVALID_MODULE_ID",
+            IntentsError::InvalidOwner => "INVALID_OWNER",
+            IntentsError::WlinAppNotSet => "WLIN_APP_NOT_SET",
+            IntentsError::ModuleNotSet => "MODULE_NOT_SET",
+            IntentsError::MatchingEngineNotSet => "MATCHING_ENGINE_NOT_SET",
+            IntentsError::PoolAlreadyExists => "POOL_ALREADY_EXISTS",
+            IntentsError::PoolNotFound => "POOL_NOT_FOUND",
+            IntentsError::TokenAppNotFound => "TOKEN_APP_NOT_FOUND",
+            IntentsError::SymbolAlreadyExists => "SYMBOL_ALREADY_EXISTS",
+            IntentsError::InvalidInitialSupply => "INVALID_INITIAL_SUPPLY",
+            IntentsError::ZeroAmount => "ZERO_AMOUNT",
+            IntentsError::MinOutNotSatisfied => "MIN_OUT_NOT_SATISFIED",
+            IntentsError::IntentNotFound => "INTENT_NOT_FOUND",
+            IntentsError::InvalidPrice => "INVALID_PRICE",
+            IntentsError::LimitPriceNotSatisfied => "LIMIT_PRICE_NOT_SATISFIED",
+            IntentsError::InsufficientBalance => "INSUFFICIENT_BALANCE",
+            IntentsError::AllowanceExceeded => "ALLOWANCE_EXCEEDED",
+            IntentsError::FaucetCapExceeded => "FAUCET_CAP_EXCEEDED",
+            IntentsError::InvalidFeeConfig => "INVALID_FEE_CONFIG",
+            IntentsError::NotCreator => "NOT_CREATOR",
+            IntentsError::Unauthorized => "UNAUTHORIZED",
+            IntentsError::AmountExceedsAvailable => "AMOUNT_EXCEEDS_AVAILABLE",
+            IntentsError::TradingPaused => "TRADING_PAUSED",
+            IntentsError::PoolNotPaused => "POOL_NOT_PAUSED",
+            IntentsError::NotIntentOwner => "NOT_INTENT_OWNER",
+            IntentsError::IntentClosed => "INTENT_CLOSED",
+            IntentsError::TradingNotStarted => "TRADING_NOT_STARTED",
+            IntentsError::LaunchBuyLimitExceeded => "LAUNCH_BUY_LIMIT_EXCEEDED",
+            IntentsError::PriceImpactTooHigh => "PRICE_IMPACT_TOO_HIGH",
+            IntentsError::TreasuryNotSet => "TREASURY_NOT_SET",
+            IntentsError::InvalidSymbol => "INVALID_SYMBOL",
+            IntentsError::ReservedSymbol => "RESERVED_SYMBOL",
+            IntentsError::InvalidName => "INVALID_NAME",
+            IntentsError::InvalidDecimals => "INVALID_DECIMALS",
+            IntentsError::ProfileTooLarge => "PROFILE_TOO_LARGE",
+            IntentsError::NotPendingCreator => "NOT_PENDING_CREATOR",
+            IntentsError::PermitExpired => "PERMIT_EXPIRED",
+            IntentsError::InvalidPermitNonce => "INVALID_PERMIT_NONCE",
+            IntentsError::NotWrappedNative => "NOT_WRAPPED_NATIVE",
+            IntentsError::FaucetBudgetExhausted => "FAUCET_BUDGET_EXHAUSTED",
+            IntentsError::InvalidAllocation => "INVALID_ALLOCATION",
+            IntentsError::NothingVested => "NOTHING_VESTED",
+            IntentsError::InvalidBatch => "INVALID_BATCH",
+            IntentsError::InvalidNonce => "INVALID_NONCE",
+            IntentsError::RequestExpired => "REQUEST_EXPIRED",
+            IntentsError::MintDisabled => "MINT_DISABLED",
+            IntentsError::WrappedNativeCrossChain => "WRAPPED_NATIVE_CROSS_CHAIN",
+        }
+    }
+
+    /// Human-readable description.
+    pub fn message(&self) -> &'static str {
+        match self {
+            IntentsError::InvalidSignatureHex => "Invalid signature hex",
+            IntentsError::InvalidSignature => "Invalid account signature",
+            IntentsError::SignatureVerificationFailed => "Signature verification failed",
+            IntentsError::MissingSignature => "Missing signature",
+            IntentsError::SignerMismatch => "Signature owner mismatch",
+            IntentsError::InvalidApplicationId => "Invalid application id",
+            IntentsError::InvalidModuleId => "Invalid module id",
+            IntentsError::InvalidOwner => "Invalid owner",
+            IntentsError::WlinAppNotSet => "wLin app id not set",
+            IntentsError::ModuleNotSet => "ModuleId not set",
+            IntentsError::MatchingEngineNotSet => "Matching-engine app id not set",
+            IntentsError::PoolAlreadyExists => "Pool already exists",
+            IntentsError::PoolNotFound => "Pool not found",
+            IntentsError::TokenAppNotFound => "Token app id not found",
+            IntentsError::SymbolAlreadyExists => "Token symbol already exists",
+            IntentsError::InvalidInitialSupply => "Initial supply must equal curve supply plus allocations",
+            IntentsError::ZeroAmount => "Amount must be > 0",
+            IntentsError::MinOutNotSatisfied => "Min out not satisfied",
+            IntentsError::IntentNotFound => "Intent not found",
+            IntentsError::InvalidPrice => "Invalid price",
+            IntentsError::LimitPriceNotSatisfied => "Limit price not satisfied",
+            IntentsError::InsufficientBalance => "Insufficient balance",
+            IntentsError::AllowanceExceeded => "Allowance exceeded",
+            IntentsError::FaucetCapExceeded => "Faucet cap exceeded",
+            IntentsError::InvalidFeeConfig => "Creator fee exceeds total fee",
+            IntentsError::NotCreator => "Caller is not the token creator",
+            IntentsError::Unauthorized => "Caller is not the app admin",
+            IntentsError::AmountExceedsAvailable => "Amount exceeds available balance",
+            IntentsError::TradingPaused => "Trading is paused",
+            IntentsError::PoolNotPaused => "Escrow withdrawal requires a paused pool",
+            IntentsError::NotIntentOwner => "Caller does not own the intent",
+            IntentsError::IntentClosed => "Intent is already closed",
+            IntentsError::TradingNotStarted => "Trading has not started for this pool",
+            IntentsError::LaunchBuyLimitExceeded => "Launch buy limit exceeded",
+            IntentsError::PriceImpactTooHigh => "Price impact too high",
+            IntentsError::TreasuryNotSet => "Treasury not set",
+            IntentsError::InvalidSymbol => "Symbol must be 2-10 letters or digits, starting with a letter",
+            IntentsError::ReservedSymbol => "Symbol is reserved",
+            IntentsError::InvalidName => "Name must be 1-32 characters",
+            IntentsError::InvalidDecimals => "Decimals must be 18",
+            IntentsError::ProfileTooLarge => "Token profile field too large",
+            IntentsError::NotPendingCreator => "Caller is not the pending creator",
+            IntentsError::PermitExpired => "Permit deadline has passed",
+            IntentsError::InvalidPermitNonce => "Invalid permit nonce",
+            IntentsError::NotWrappedNative => "Token does not wrap native tokens",
+            IntentsError::FaucetBudgetExhausted => "Faucet budget for this window is spent",
+            IntentsError::InvalidAllocation => "Invalid token allocations",
+            IntentsError::NothingVested => "No vested tokens to claim",
+            IntentsError::InvalidBatch => {
+                "Batch must have 1-100 recipients with a representable total"
+            }
+            IntentsError::InvalidNonce => "Invalid request nonce",
+            IntentsError::RequestExpired => "Request deadline has passed",
+            IntentsError::MintDisabled => "Minting is disabled for this token",
+            IntentsError::WrappedNativeCrossChain => "Wrapped native tokens cannot move across chains",
+        }
+    }
+}
+
+impl fmt::Display for IntentsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for IntentsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permit(signature_hex: &str) -> Permit {
+        Permit {
+            allowance: Amount::from_tokens(10),
+            deadline: Timestamp::from(1_000),
+            nonce: 0,
+            signature_hex: signature_hex.to_string(),
+        }
+    }
+
+    #[test]
+    fn invalid_batch_message_states_the_limit() {
+        let limit = format!("1-{MAX_BATCH_RECIPIENTS} recipients");
+        assert!(IntentsError::InvalidBatch.message().contains(&limit));
+    }
+
+    #[test]
+    fn permit_requires_a_signature() {
+        assert_eq!(permit("").ensure_signed(), Err(IntentsError::MissingSignature));
+        assert_eq!(permit("ab").ensure_signed(), Ok(()));
+    }
+
+    fn allocation(byte: u8, tokens: u128, cliff_secs: u64, vesting_secs: u64) -> Allocation {
+        Allocation {
+            beneficiary: AccountOwner::Address20([byte; 20]),
+            amount: Amount::from_tokens(tokens),
+            cliff_secs,
+            vesting_secs,
+        }
+    }
+
+    #[test]
+    fn validate_all_sums_allocations() {
+        assert_eq!(Allocation::validate_all(&[]), Ok(Amount::ZERO));
+        let allocations = [allocation(1, 100, 0, 0), allocation(2, 50, 10, 20)];
+        assert_eq!(Allocation::validate_all(&allocations), Ok(Amount::from_tokens(150)));
+        let max: Vec<_> = (0..MAX_ALLOCATIONS as u8).map(|i| allocation(i, 1, 0, 1)).collect();
+        assert_eq!(
+            Allocation::validate_all(&max),
+            Ok(Amount::from_tokens(MAX_ALLOCATIONS as u128))
+        );
+    }
+
+    #[test]
+    fn validate_all_rejects_malformed_allocations() {
+        let cases = [
+            vec![allocation(1, 0, 0, 0)],
+            vec![allocation(1, 1, 20, 10)],
+            vec![allocation(1, 1, 0, 0), allocation(1, 2, 0, 0)],
+            (0..=MAX_ALLOCATIONS as u8).map(|i| allocation(i, 1, 0, 1)).collect(),
+            vec![
+                Allocation { amount: Amount::MAX, ..allocation(1, 0, 0, 0) },
+                allocation(2, 1, 0, 0),
+            ],
+        ];
+        for allocations in cases {
+            assert_eq!(
+                Allocation::validate_all(&allocations),
+                Err(IntentsError::InvalidAllocation)
+            );
+        }
+    }
+
+    const SECOND: u64 = 1_000_000;
+
+    fn protection() -> LaunchProtection {
+        LaunchProtection {
+            trading_delay_secs: 60,
+            protection_window_secs: 300,
+            max_buy_per_owner: Amount::from_tokens(1_500),
+            max_price_impact_bps: 500,
+        }
+    }
+
+    #[test]
+    fn launch_window_opens_after_delay_and_expires() {
+        let created_at = Timestamp::from(1_000 * SECOND);
+        let at = |secs: u64| Timestamp::from((1_000 + secs) * SECOND);
+        assert_eq!(
+            protection().is_protected(created_at, at(59)),
+            Err(IntentsError::TradingNotStarted)
+        );
+        assert_eq!(protection().is_protected(created_at, at(60)), Ok(true));
+        assert_eq!(protection().is_protected(created_at, at(359)), Ok(true));
+        assert_eq!(protection().is_protected(created_at, at(360)), Ok(false));
+        assert_eq!(LaunchProtection::default().is_protected(created_at, at(0)), Ok(false));
+    }
+
+    #[test]
+    fn launch_buy_cap_is_cumulative_per_owner() {
+        let tokens = Amount::from_tokens;
+        assert_eq!(protection().check_buy(Amount::ZERO, tokens(1_000), 0), Ok(tokens(1_000)));
+        assert_eq!(protection().check_buy(tokens(1_000), tokens(500), 0), Ok(tokens(1_500)));
+        assert_eq!(
+            protection().check_buy(tokens(1_000), tokens(501), 0),
+            Err(IntentsError::LaunchBuyLimitExceeded)
+        );
+        let uncapped = LaunchProtection { max_buy_per_owner: Amount::ZERO, ..protection() };
+        assert_eq!(uncapped.check_buy(tokens(1_000_000), tokens(1), 0), Ok(tokens(1_000_001)));
+    }
+
+    #[test]
+    fn launch_impact_cap_rejects_large_moves() {
+        let amount = Amount::from_tokens(1);
+        assert_eq!(protection().check_buy(Amount::ZERO, amount, 500), Ok(amount));
+        assert_eq!(
+            protection().check_buy(Amount::ZERO, amount, 501),
+            Err(IntentsError::PriceImpactTooHigh)
+        );
+        let unlimited = LaunchProtection { max_price_impact_bps: 0, ..protection() };
+        assert_eq!(unlimited.check_buy(Amount::ZERO, amount, 10_000), Ok(amount));
+    }
+
+    #[test]
+    fn validate_symbol_normalizes() {
+        assert_eq!(validate_symbol(" tst "), Ok("TST".to_string()));
+        assert_eq!(validate_symbol("a1"), Ok("A1".to_string()));
+        assert_eq!(validate_symbol("ABCDEFGHIJ"), Ok("ABCDEFGHIJ".to_string()));
+    }
+
+    #[test]
+    fn validate_symbol_rejects_bad_grammar() {
+        for symbol in ["", "A", "ABCDEFGHIJK", "1ABC", "AB-C", "A B", "ÄBC", "\u{1F680}X"] {
+            assert_eq!(validate_symbol(symbol), Err(IntentsError::InvalidSymbol), "{symbol:?}");
+        }
+    }
+
+    #[test]
+    fn price_from_str_parses_fixed_point() {
+        assert_eq!("1".parse::<Price>(), Ok(Price::from_raw(Price::ONE_RAW)));
+        assert_eq!("1.".parse::<Price>(), Ok(Price::from_raw(Price::ONE_RAW)));
+        assert_eq!("0.0001".parse::<Price>(), Ok(Price::from_raw(100_000_000_000_000)));
+        assert_eq!("2.5".parse::<Price>(), Ok(Price::from_raw(25 * Price::ONE_RAW / 10)));
+        assert_eq!("0.000000000000000001".parse::<Price>(), Ok(Price::from_raw(1)));
+        assert_eq!("0".parse::<Price>(), Ok(Price::ZERO));
+    }
+
+    #[test]
+    fn price_from_str_rejects_malformed_input() {
+        for input in ["", ".5", "-1", "+1", "1e3", "1.2.3", " 1", "0.0000000000000000001"] {
+            assert_eq!(input.parse::<Price>(), Err(IntentsError::InvalidPrice), "{input:?}");
+        }
+        // u128::MAX raw units is about 3.4e20 whole units.
+        assert_eq!(
+            "400000000000000000000".parse::<Price>(),
+            Err(IntentsError::InvalidPrice)
+        );
+    }
+
+    #[test]
+    fn price_display_trims_trailing_zeros() {
+        assert_eq!(Price::ZERO.to_string(), "0");
+        assert_eq!(Price::from_raw(Price::ONE_RAW).to_string(), "1");
+        assert_eq!(Price::from_raw(15 * Price::ONE_RAW / 10).to_string(), "1.5");
+        assert_eq!(Price::from_raw(100_000_000_000_000).to_string(), "0.0001");
+        assert_eq!(Price::from_raw(1).to_string(), "0.000000000000000001");
+    }
+
+    #[test]
+    fn price_display_round_trips() {
+        for raw in [0, 1, 42, Price::ONE_RAW, 123_456_789 * Price::ONE_RAW + 7, u128::MAX] {
+            let price = Price::from_raw(raw);
+            assert_eq!(price.to_string().parse::<Price>(), Ok(price));
+        }
+    }
+}
@@ -2,17 +2,26 @@
 
 //! Contract logic for the token-factory app.
 
-use linera_base::crypto::AccountSignature;
 use linera_base::abi::WithContractAbi;
 use linera_base::identifiers::{AccountOwner, ApplicationId, ModuleId};
-use fungible_token::{FungibleTokenAbi, InitialStateBuilder, Parameters};
+use fungible_token::{
+    Account, FungibleTokenAbi, InitialStateBuilder, Operation as FungibleOperation, Parameters,
+    SignedTransferFromRequest, TransferFromRequest,
+};
+use linera_sdk::linera_base_types::Amount;
 use linera_sdk::{contract::ContractRuntime, Contract, views::{RootView, View}};
 use serde_json::Value;
 
-use matching_engine::{fixed_pool_config, Operation as MatchingEngineOperation};
-use shared_types::SignedCreateTokenRequest;
+use matching_engine::{
+    fixed_pool_config, MatchingEngineResponse, Operation as MatchingEngineOperation,
+};
+use shared_types::{
+    normalize_symbol, validate_symbol, Allocation, IntentsError, IntentsResult, SignedAcceptCreatorRequest,
+    SignedCreateTokenRequest, SignedTransferCreatorRequest, SignedUpdateTokenProfileRequest,
+    TradeReceipt,
+};
 
-use token_factory::{Operation, TokenFactoryAbi};
+use token_factory::{Operation, TokenFactoryAbi, TokenFactoryResponse};
 use token_factory::state::TokenFactoryState;
 
 pub struct TokenFactoryContract {
@@ -39,14 +48,21 @@ impl Contract for TokenFactoryContract {
         TokenFactoryContract { state, runtime }
     }
 
-    async fn instantiate(&mut self, _state: Self::InstantiationArgument) {}
+    async fn instantiate(&mut self, _state: Self::InstantiationArgument) {
+        let admin = self.runtime.authenticated_signer();
+        self.state.admin.set(admin);
+        for symbol in DEFAULT_RESERVED_SYMBOLS {
+            self.state
+                .reserved_symbols
+                .insert(&symbol.to_string(), true)
+                .expect("Failed to reserve symbol");
+        }
+    }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
-        match operation {
-            Operation::SetModule { module_id } => self.set_module(module_id),
-            Operation::SetMatchingEngine { app_id } => self.set_matching_engine(app_id),
-            Operation::CreateToken { request } => self.create_token(request).await,
-        }
+        self.handle_operation(operation)
+            .await
+            .unwrap_or_else(|error| panic!("{error}"))
     }
 
     async fn execute_message(&mut self, _message: Self::Message) {}
@@ -56,24 +72,117 @@ impl Contract for TokenFactoryContract {
     }
 }
 
+/// Symbols reserved at instantiation (the admin can release them).
+const DEFAULT_RESERVED_SYMBOLS: [&str; 3] = ["WLIN", "LIN", "LINERA"];
+
 impl TokenFactoryContract {
-    fn set_module(&mut self, module_id: String) {
-        let parsed = module_id.parse::<ModuleId>().expect("Invalid module id");
+    async fn handle_operation(&mut self, operation: Operation) -> IntentsResult<TokenFactoryResponse> {
+        match operation {
+            Operation::SetModule { module_id } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.set_module(module_id)?;
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::SetMatchingEngine { app_id } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.set_matching_engine(app_id)?;
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::SetWlinApp { app_id } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                let parsed = app_id
+                    .parse::<ApplicationId>()
+                    .map_err(|_| IntentsError::InvalidApplicationId)?;
+                self.state.wlin_app_id.set(Some(parsed));
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::SetCreationFee { amount } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.creation_fee.set(amount);
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::SetTreasury { owner } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                self.state.treasury.set(Some(owner));
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::ReserveSymbol { symbol } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                let symbol = validate_symbol(&symbol)?;
+                self.state
+                    .reserved_symbols
+                    .insert(&symbol, true)
+                    .expect("Failed to reserve symbol");
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::ReleaseSymbol { symbol } => {
+                auth::ensure_admin(&mut self.runtime, *self.state.admin.get())?;
+                let symbol = validate_symbol(&symbol)?;
+                self.state
+                    .reserved_symbols
+                    .remove(&symbol)
+                    .expect("Failed to release symbol");
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::CreateToken { request } => {
+                let (app_id, dev_buy) = self.create_token(request).await?;
+                Ok(TokenFactoryResponse::TokenCreated { app_id, dev_buy })
+            }
+            Operation::UpdateTokenProfile { request } => {
+                self.update_token_profile(request).await?;
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::TransferCreator { request } => {
+                self.transfer_creator(request).await?;
+                Ok(TokenFactoryResponse::Ok)
+            }
+            Operation::AcceptCreator { request } => {
+                self.accept_creator(request).await?;
+                Ok(TokenFactoryResponse::Ok)
+            }
+        }
+    }
+
+    fn set_module(&mut self, module_id: String) -> IntentsResult<()> {
+        let parsed = module_id
+            .parse::<ModuleId>()
+            .map_err(|_| IntentsError::InvalidModuleId)?;
         self.state.module_id.set(Some(parsed));
+        Ok(())
     }
 
-    fn set_matching_engine(&mut self, app_id: String) {
-        let parsed = app_id.parse::<ApplicationId>().expect("Invalid app id");
+    fn set_matching_engine(&mut self, app_id: String) -> IntentsResult<()> {
+        let parsed = app_id
+            .parse::<ApplicationId>()
+            .map_err(|_| IntentsError::InvalidApplicationId)?;
         self.state.matching_engine_app_id.set(Some(parsed));
+        Ok(())
     }
 
-    async fn create_token(&mut self, request: SignedCreateTokenRequest) {
-        let owner = self.verify_signature(&request.payload, &request.signature_hex);
-        if request.payload.owner != owner {
-            panic!("Signature owner mismatch");
-        }
+    async fn create_token(
+        &mut self,
+        request: SignedCreateTokenRequest,
+    ) -> IntentsResult<(ApplicationId, Option<TradeReceipt>)> {
+        let owner = auth::authenticate_typed(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
 
-        let symbol = request.payload.metadata.symbol.clone();
+        let mut metadata = request.payload.metadata;
+        metadata.validate()?;
+        let symbol = metadata.symbol.clone();
+        if self
+            .state
+            .reserved_symbols
+            .contains_key(&symbol)
+            .await
+            .expect("Failed to read reserved symbols")
+        {
+            return Err(IntentsError::ReservedSymbol);
+        }
         if self
             .state
             .token_app_id_by_symbol
@@ -82,12 +191,13 @@ impl TokenFactoryContract {
             .expect("Failed to read token registry")
             .is_some()
         {
-            panic!("Token symbol already exists");
+            return Err(IntentsError::SymbolAlreadyExists);
         }
 
         let config = fixed_pool_config();
-        if request.payload.initial_supply != config.total_curve_supply {
-            panic!("Initial supply must match fixed curve supply");
+        let allocated = Allocation::validate_all(&request.payload.allocations)?;
+        if request.payload.initial_supply != config.total_curve_supply.saturating_add(allocated) {
+            return Err(IntentsError::InvalidInitialSupply);
         }
 
         let module_id = self
@@ -95,22 +205,28 @@ impl TokenFactoryContract {
             .module_id
             .get()
             .clone()
-            .expect("ModuleId not set");
+            .ok_or(IntentsError::ModuleNotSet)?;
 
         let matching_engine_app_id = self
             .state
             .matching_engine_app_id
             .get()
             .clone()
-            .expect("Matching-engine app id not set");
+            .ok_or(IntentsError::MatchingEngineNotSet)?;
 
-        // Initialize token app with fixed curve supply owned by matching-engine app.
+        self.charge_creation_fee(owner)?;
+
+        // Initialize token app with fixed curve supply owned by matching-engine app;
+        // allocations stay locked in the token app until vested.
         let mut initial_state = InitialStateBuilder::default();
         let pool_owner: AccountOwner = matching_engine_app_id.into();
         initial_state = initial_state.with_account(pool_owner, config.total_curve_supply);
+        for allocation in request.payload.allocations {
+            initial_state = initial_state.with_vesting(allocation);
+        }
 
         let params = Parameters::new(
-            request.payload.metadata.symbol.clone(),
+            symbol.clone(),
             None,
             Some(matching_engine_app_id),
         );
@@ -131,7 +247,7 @@ impl TokenFactoryContract {
             .expect("Failed to insert token app id");
         self.state
             .token_metadata_by_app
-            .insert(&application_id, request.payload.metadata.clone())
+            .insert(&application_id, metadata)
             .expect("Failed to insert token metadata");
         self.state
             .creator_by_symbol
@@ -149,23 +265,177 @@ impl TokenFactoryContract {
             symbol,
             token_app_id: token_app_id_str,
             config,
+            creator: owner,
+            dev_buy: request.payload.dev_buy,
         };
-        self.runtime
+        let response = self
+            .runtime
             .call_application(true, matching_engine_app_id.with_abi::<matching_engine::MatchingEngineAbi>(), &op);
+        let dev_buy = match response {
+            MatchingEngineResponse::PoolCreated { dev_buy } => dev_buy,
+            _ => None,
+        };
+        Ok((application_id, dev_buy))
+    }
+
+    async fn update_token_profile(
+        &mut self,
+        request: SignedUpdateTokenProfileRequest,
+    ) -> IntentsResult<()> {
+        let owner = auth::authenticate(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
+        let symbol = normalize_symbol(&request.payload.symbol);
+        self.ensure_creator(&symbol, owner).await?;
+        request.payload.profile.validate()?;
+        let nonce = self
+            .state
+            .profile_nonce_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read profile nonce")
+            .unwrap_or_default();
+        if request.payload.nonce != nonce {
+            return Err(IntentsError::InvalidNonce);
+        }
+        self.state
+            .profile_nonce_by_symbol
+            .insert(&symbol, nonce + 1)
+            .expect("Failed to update profile nonce");
+        self.state
+            .token_profile_by_symbol
+            .insert(&symbol, request.payload.profile)
+            .expect("Failed to update token profile");
+        Ok(())
+    }
+
+    async fn transfer_creator(&mut self, request: SignedTransferCreatorRequest) -> IntentsResult<()> {
+        let owner = auth::authenticate(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
+        let symbol = normalize_symbol(&request.payload.symbol);
+        self.ensure_creator(&symbol, owner).await?;
+        self.consume_creator_nonce(&symbol, request.payload.nonce).await?;
+        self.state
+            .pending_creator_by_symbol
+            .insert(&symbol, request.payload.new_creator)
+            .expect("Failed to record pending creator");
+        Ok(())
     }
 
-    fn verify_signature<T: std::fmt::Debug>(&self, payload: &T, signature_hex: &str) -> AccountOwner
-    where
-        for<'de> T: linera_base::crypto::BcsSignable<'de>,
-    {
-        let bytes = hex::decode(signature_hex).expect("Invalid signature hex");
-        let signature = AccountSignature::try_from(bytes.as_slice())
-            .expect("Invalid account signature");
-        signature.verify(payload).expect("Signature verification failed");
-        match signature {
-            AccountSignature::Ed25519 { public_key, .. } => AccountOwner::from(public_key),
-            AccountSignature::Secp256k1 { public_key, .. } => AccountOwner::from(public_key),
-            AccountSignature::EvmSecp256k1 { address, .. } => AccountOwner::Address20(address),
+    async fn accept_creator(&mut self, request: SignedAcceptCreatorRequest) -> IntentsResult<()> {
+        let owner = auth::authenticate(
+            &mut self.runtime,
+            &request.payload,
+            request.payload.owner,
+            &request.signature_hex,
+            None,
+        )?;
+        let symbol = normalize_symbol(&request.payload.symbol);
+        let pending = self
+            .state
+            .pending_creator_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read pending creator");
+        if pending != Some(owner) {
+            return Err(IntentsError::NotPendingCreator);
         }
+        self.consume_creator_nonce(&symbol, request.payload.nonce).await?;
+        self.state
+            .pending_creator_by_symbol
+            .remove(&symbol)
+            .expect("Failed to clear pending creator");
+        self.state
+            .creator_by_symbol
+            .insert(&symbol, owner)
+            .expect("Failed to update creator");
+
+        // Move the creator fee share in the matching engine as well.
+        let matching_engine_app_id = self
+            .state
+            .matching_engine_app_id
+            .get()
+            .ok_or(IntentsError::MatchingEngineNotSet)?
+            .with_abi::<matching_engine::MatchingEngineAbi>();
+        let op = MatchingEngineOperation::UpdateCreator { symbol, creator: owner };
+        self.runtime.call_application(true, matching_engine_app_id, &op);
+        Ok(())
     }
+
+    /// Checks `nonce` against the symbol's creator nonce and advances it, so a
+    /// signed hand-over or acceptance can only be executed once.
+    async fn consume_creator_nonce(&mut self, symbol: &str, nonce: u64) -> IntentsResult<()> {
+        let current = self
+            .state
+            .creator_nonce_by_symbol
+            .get(symbol)
+            .await
+            .expect("Failed to read creator nonce")
+            .unwrap_or_default();
+        if nonce != current {
+            return Err(IntentsError::InvalidNonce);
+        }
+        self.state
+            .creator_nonce_by_symbol
+            .insert(symbol, current + 1)
+            .expect("Failed to update creator nonce");
+        Ok(())
+    }
+
+    /// Fails unless `owner` is the recorded creator of `symbol`.
+    async fn ensure_creator(&self, symbol: &str, owner: AccountOwner) -> IntentsResult<()> {
+        let creator = self
+            .state
+            .creator_by_symbol
+            .get(symbol)
+            .await
+            .expect("Failed to read creator")
+            .ok_or(IntentsError::TokenAppNotFound)?;
+        if creator != owner {
+            return Err(IntentsError::NotCreator);
+        }
+        Ok(())
+    }
+
+    /// Pulls the creation fee from `creator`'s wLin allowance to the treasury.
+    fn charge_creation_fee(&mut self, creator: AccountOwner) -> IntentsResult<()> {
+        let fee = *self.state.creation_fee.get();
+        if fee == Amount::ZERO {
+            return Ok(());
+        }
+        let treasury = self.state.treasury.get().ok_or(IntentsError::TreasuryNotSet)?;
+        let wlin = self
+            .state
+            .wlin_app_id
+            .get()
+            .ok_or(IntentsError::WlinAppNotSet)?
+            .with_abi::<FungibleTokenAbi>();
+        let app_owner: AccountOwner = self.runtime.application_id().into();
+        let op = FungibleOperation::TransferFrom {
+            request: SignedTransferFromRequest {
+                payload: TransferFromRequest {
+                    owner: creator,
+                    spender: app_owner,
+                    amount: fee,
+                    target_account: Account {
+                        chain_id: self.runtime.chain_id(),
+                        owner: treasury,
+                    },
+                },
+                signature_hex: String::new(),
+            },
+        };
+        self.runtime.call_application(true, wlin, &op);
+        Ok(())
+    }
+
 }
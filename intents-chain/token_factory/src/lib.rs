@@ -5,10 +5,17 @@
 
 pub mod state;
 
-use linera_sdk::{graphql::GraphQLMutationRoot, linera_base_types::{ContractAbi, ServiceAbi}};
+use async_graphql::SimpleObject;
+use linera_sdk::{
+    graphql::GraphQLMutationRoot,
+    linera_base_types::{AccountOwner, Amount, ApplicationId, ContractAbi, ServiceAbi},
+};
 use serde::{Deserialize, Serialize};
 
-use shared_types::SignedCreateTokenRequest;
+use shared_types::{
+    SignedAcceptCreatorRequest, SignedCreateTokenRequest, SignedTransferCreatorRequest,
+    SignedUpdateTokenProfileRequest, TokenMetadata, TokenProfile, TradeReceipt,
+};
 
 /// ABI definition for the token-factory app.
 pub struct TokenFactoryAbi;
@@ -16,17 +23,75 @@ pub struct TokenFactoryAbi;
 /// Operations executed by the token-factory app.
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
-    /// Operator records the fungible-token ModuleId.
+    /// Admin records the fungible-token ModuleId.
     SetModule { module_id: String },
-    /// Operator sets the matching-engine app id.
+    /// Admin sets the matching-engine app id.
     SetMatchingEngine { app_id: String },
-    /// User requests creation of a new token app instance.
+    /// Admin sets the wLin app id used for the creation fee.
+    SetWlinApp { app_id: String },
+    /// Admin sets the creation fee in wLin (zero disables it).
+    SetCreationFee { amount: Amount },
+    /// Admin sets the account receiving creation fees.
+    SetTreasury { owner: AccountOwner },
+    /// Admin reserves a symbol so it cannot be created.
+    ReserveSymbol { symbol: String },
+    /// Admin releases a previously reserved symbol.
+    ReleaseSymbol { symbol: String },
+    /// User requests creation of a new token app instance. The creation fee
+    /// is pulled from the creator's wLin allowance to the factory.
     CreateToken { request: SignedCreateTokenRequest },
+    /// Token creator replaces the token's profile (description, links).
+    UpdateTokenProfile { request: SignedUpdateTokenProfileRequest },
+    /// Token creator proposes a new creator (replaces any pending proposal).
+    TransferCreator { request: SignedTransferCreatorRequest },
+    /// Proposed creator accepts; fee claims and profile edits move to them.
+    AcceptCreator { request: SignedAcceptCreatorRequest },
+}
+
+/// Token metadata merged with its creator and editable profile, as returned by
+/// the `tokenMetadata` query.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct TokenDetails {
+    /// Display name.
+    pub name: String,
+    /// Token symbol (normalized).
+    pub symbol: String,
+    /// Token decimals.
+    pub decimals: u8,
+    /// Current token creator.
+    pub creator: Option<AccountOwner>,
+    /// Creator-editable profile (empty fields when never set).
+    pub profile: TokenProfile,
+}
+
+impl TokenDetails {
+    pub fn new(metadata: TokenMetadata, creator: Option<AccountOwner>, profile: TokenProfile) -> Self {
+        Self {
+            name: metadata.name,
+            symbol: metadata.symbol,
+            decimals: metadata.decimals,
+            creator,
+            profile,
+        }
+    }
+}
+
+/// Typed result of a token-factory operation.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum TokenFactoryResponse {
+    /// Configuration operations that produce no data.
+    Ok,
+    /// A token app instance was created and its pool opened; `dev_buy` holds
+    /// the creator's initial buy, if requested.
+    TokenCreated {
+        app_id: ApplicationId,
+        dev_buy: Option<TradeReceipt>,
+    },
 }
 
 impl ContractAbi for TokenFactoryAbi {
     type Operation = Operation;
-    type Response = ();
+    type Response = TokenFactoryResponse;
 }
 
 impl ServiceAbi for TokenFactoryAbi {
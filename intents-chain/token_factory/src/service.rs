@@ -12,8 +12,9 @@ use linera_sdk::{
     Service, ServiceRuntime,
 };
 
-use token_factory::{Operation, TokenFactoryAbi};
+use token_factory::{Operation, TokenDetails, TokenFactoryAbi};
 use token_factory::state::TokenFactoryState;
+use shared_types::normalize_symbol;
 
 #[derive(Clone)]
 pub struct TokenFactoryService {
@@ -65,7 +66,20 @@ impl TokenFactoryService {
             .map(|id| id.to_string())
     }
 
+    async fn wlin_app_id(&self) -> Option<String> {
+        self.state.wlin_app_id.get().as_ref().map(|id| id.to_string())
+    }
+
+    async fn creation_fee(&self) -> linera_sdk::linera_base_types::Amount {
+        *self.state.creation_fee.get()
+    }
+
+    async fn treasury(&self) -> Option<linera_sdk::linera_base_types::AccountOwner> {
+        *self.state.treasury.get()
+    }
+
     async fn token_app_id(&self, symbol: String) -> Option<String> {
+        let symbol = normalize_symbol(&symbol);
         self.state
             .token_app_id_by_symbol
             .get(&symbol)
@@ -74,17 +88,73 @@ impl TokenFactoryService {
             .map(|id| id.to_string())
     }
 
-    async fn token_metadata(&self, symbol: String) -> Option<shared_types::TokenMetadata> {
+    async fn pending_creator(&self, symbol: String) -> Option<linera_sdk::linera_base_types::AccountOwner> {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .pending_creator_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read pending creator")
+    }
+
+    /// Nonce the next `TransferCreator` / `AcceptCreator` request for `symbol` must carry.
+    async fn creator_nonce(&self, symbol: String) -> u64 {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .creator_nonce_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read creator nonce")
+            .unwrap_or_default()
+    }
+
+    /// Nonce the next `UpdateTokenProfile` request for `symbol` must carry.
+    async fn profile_nonce(&self, symbol: String) -> u64 {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .profile_nonce_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read profile nonce")
+            .unwrap_or_default()
+    }
+
+    async fn is_symbol_reserved(&self, symbol: String) -> bool {
+        let symbol = normalize_symbol(&symbol);
+        self.state
+            .reserved_symbols
+            .contains_key(&symbol)
+            .await
+            .expect("Failed to read reserved symbols")
+    }
+
+    async fn token_metadata(&self, symbol: String) -> Option<TokenDetails> {
+        let symbol = normalize_symbol(&symbol);
         let app_id = self
             .state
             .token_app_id_by_symbol
             .get(&symbol)
             .await
             .expect("Failed to read token app id")?;
-        self.state
+        let metadata = self
+            .state
             .token_metadata_by_app
             .get(&app_id)
             .await
-            .expect("Failed to read token metadata")
+            .expect("Failed to read token metadata")?;
+        let creator = self
+            .state
+            .creator_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read creator");
+        let profile = self
+            .state
+            .token_profile_by_symbol
+            .get(&symbol)
+            .await
+            .expect("Failed to read token profile")
+            .unwrap_or_default();
+        Some(TokenDetails::new(metadata, creator, profile))
     }
 }
@@ -1,19 +1,27 @@
 //! On-chain state for the token-factory app.
 
 use linera_base::identifiers::{ApplicationId, ModuleId};
-use linera_sdk::linera_base_types::AccountOwner;
+use linera_sdk::linera_base_types::{AccountOwner, Amount};
 use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
 
-use shared_types::TokenMetadata;
+use shared_types::{TokenMetadata, TokenProfile};
 
 /// Persistent storage for token-factory app.
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct TokenFactoryState {
+    /// Admin allowed to change fee settings (signer at instantiation).
+    pub admin: RegisterView<Option<AccountOwner>>,
     /// Published fungible-token module id (bytecode).
     pub module_id: RegisterView<Option<ModuleId>>,
     /// Matching-engine app id (same chain).
     pub matching_engine_app_id: RegisterView<Option<ApplicationId>>,
+    /// wLin app id used to charge the creation fee.
+    pub wlin_app_id: RegisterView<Option<ApplicationId>>,
+    /// Token creation fee in wLin (zero = free).
+    pub creation_fee: RegisterView<Amount>,
+    /// Account receiving creation fees.
+    pub treasury: RegisterView<Option<AccountOwner>>,
 
     /// Registry: token symbol -> app id.
     pub token_app_id_by_symbol: MapView<String, ApplicationId>,
@@ -21,4 +29,14 @@ pub struct TokenFactoryState {
     pub token_metadata_by_app: MapView<ApplicationId, TokenMetadata>,
     /// Registry: token symbol -> creator.
     pub creator_by_symbol: MapView<String, AccountOwner>,
+    /// Proposed creator per symbol awaiting `AcceptCreator`.
+    pub pending_creator_by_symbol: MapView<String, AccountOwner>,
+    /// Next expected nonce of `TransferCreator` / `AcceptCreator` per symbol.
+    pub creator_nonce_by_symbol: MapView<String, u64>,
+    /// Registry: token symbol -> creator-editable profile.
+    pub token_profile_by_symbol: MapView<String, TokenProfile>,
+    /// Next expected `UpdateTokenProfileRequest::nonce` per symbol.
+    pub profile_nonce_by_symbol: MapView<String, u64>,
+    /// Admin-managed symbols that cannot be created (normalized form).
+    pub reserved_symbols: MapView<String, bool>,
 }
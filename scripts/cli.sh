@@ -10,7 +10,7 @@ OWNER="0x49c2f87001ec3e39ea5a4dbd115e404c4d4a4641e83c9a60dc3d9e77778f72c1"
 FEE_DESTINATION="$OWNER"
 TOKEN_NAME="${TOKEN_NAME:-Test}"
 TOKEN_SYMBOL="${TOKEN_SYMBOL:-TST}"
-TOKEN_DECIMALS="${TOKEN_DECIMALS:-9}"
+TOKEN_DECIMALS="${TOKEN_DECIMALS:-18}"
 TOKEN_INITIAL_SUPPLY="${TOKEN_INITIAL_SUPPLY:-800000000}"
 WALLET_USER2="./wallets/user2_wallet.json"
 KEYSTORE_USER2="./wallets/user2_keystore.json"
@@ -33,17 +33,57 @@ TST_APP_ID="5b0d02cccfeee39d79b035f27fb61ced602c7cfe3b32661831520e009e5cad15"
 CREATE_TOKEN_SIG="003804e6092410173ca440e60265daf8240ba12f81102ffce32f2d0f40cd8eb574f78ac8580437398b94d61354deeeff017fadcd4018691fdef3c767c554c12d00f30b8cc9ed206443cbe13949259caec51fb694ad09d8c4994f880d9260143973"
 WLIN_APPROVE_SIG="00a628560d3a6891911f5b18214d5159e4026fff87a9dd6eeb0fc4f6c780fbe6ecb8003cd716e264f1e5fa76315754a3c7464d1444678914e24dc6c524f61b490cf30b8cc9ed206443cbe13949259caec51fb694ad09d8c4994f880d9260143973"
 TST_APPROVE_SIG="00a628560d3a6891911f5b18214d5159e4026fff87a9dd6eeb0fc4f6c780fbe6ecb8003cd716e264f1e5fa76315754a3c7464d1444678914e24dc6c524f61b490cf30b8cc9ed206443cbe13949259caec51fb694ad09d8c4994f880d9260143973"
-BUY_SIG="00ad940203d3e235dd3e3cd20b3c9d625afe74046e498d612212f83befb0b292f377766ec2d54abcac9a8ff40041b0d7b1b9aa0e4c56f52c9ac785be30b6ce740cf30b8cc9ed206443cbe13949259caec51fb694ad09d8c4994f880d9260143973"
-SELL_SIG="007cb8d11b1b71ffacc0a76f20e304dc05cf79f4c770279860ee768d4aeba9a7cbc9c17e9ccc8a0f7b5d38f44e477e7c2c272bf8b0e598f2d7f4948d90441d820ff30b8cc9ed206443cbe13949259caec51fb694ad09d8c4994f880d9260143973"
 CREATE_TOKEN_SIG_USER2="00359c4b171aa0081167d479b3ea5f4a445b2af9f6981ea860b342c064b5326a1432b4bb58d22e89c3a38b10181c810ce6faa87d8699837b0a4fddc9d8cca6d90777618bfc77ce17e46fe3d7c9fb9f5a983eb527ee7bec09fb544bc612b9f6f0a6"
 WLIN_APPROVE_SIG_USER2="0085c21ede00765f25e72063b0ef248f255caf26d304acdf1cc0baaa47901e3e1f301efcd4d74a53924a7e094a999078a4ae043da006954f97d21322157be73c0577618bfc77ce17e46fe3d7c9fb9f5a983eb527ee7bec09fb544bc612b9f6f0a6"
 TST_APPROVE_SIG_USER2="0085c21ede00765f25e72063b0ef248f255caf26d304acdf1cc0baaa47901e3e1f301efcd4d74a53924a7e094a999078a4ae043da006954f97d21322157be73c0577618bfc77ce17e46fe3d7c9fb9f5a983eb527ee7bec09fb544bc612b9f6f0a6"
-BUY_SIG_USER2="00159a3bdd2d5ae2f39225b668aef72e917ae54bf11dc71c522a0084bfb57bb32dbbe78cd58906f10eee98f94ad55666e4409fbe3454745bac50ae80968b216a0d77618bfc77ce17e46fe3d7c9fb9f5a983eb527ee7bec09fb544bc612b9f6f0a6"
-SELL_SIG_USER2="00702cd3e81c9ff63e5a4a9135fb6eb4099d1df6fbe78fc8050219866ad3dc3c7ad29638b9ff5f263bbf50ceed24503bc0736f95dbadc5748100b5b0cc99a3840c77618bfc77ce17e46fe3d7c9fb9f5a983eb527ee7bec09fb544bc612b9f6f0a6"
-INTENT_SIG="__INTENT_SIG__"
+
+# Trades and intents are signed at run time: their deadline is TRADE_TTL_SECS
+# from now and their nonce is the owner's current `tradeNonce`. Set SECRET_HEX
+# (and SECRET_HEX_USER2) from scripts/gen_keypair, or pass BUY_SIG / SELL_SIG /
+# INTENT_SIG (with TRADE_DEADLINE and TRADE_NONCE) to submit pre-signed payloads.
+SECRET_HEX="${SECRET_HEX:-}"
+SECRET_HEX_USER2="${SECRET_HEX_USER2:-}"
+TRADE_TTL_SECS="${TRADE_TTL_SECS:-600}"
+TRADE_DEADLINE="${TRADE_DEADLINE:-$(( ($(date +%s) + TRADE_TTL_SECS) * 1000000 ))}"
+
 INTENT_ID="__INTENT_ID__"
 FILL_AMOUNT="${FILL_AMOUNT:-0}"
 
+# Prints the owner's next trade nonce (shared by buys, sells and intents).
+trade_nonce() {
+  curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
+    -H 'Content-Type: application/json' \
+    --data-raw "{\"query\":\"query { tradeNonce(owner: \\\"$1\\\") }\"}" \
+    | python3 -c 'import json, sys; print(json.load(sys.stdin)["data"]["tradeNonce"])'
+}
+
+# sign OWNER SECRET_HEX KEY [VAR=VALUE...]: prints the KEY line of scripts/sign_create_token.
+sign() {
+  local owner="$1" secret="$2" key="$3"
+  shift 3
+  if [ -z "$secret" ]; then
+    echo "Missing secret key for $owner (set SECRET_HEX / SECRET_HEX_USER2 or pass $key)" >&2
+    exit 1
+  fi
+  env OWNER="$owner" SECRET_HEX="$secret" NAME="$TOKEN_NAME" SYMBOL="$TOKEN_SYMBOL" \
+    DECIMALS="$TOKEN_DECIMALS" INITIAL_SUPPLY="$TOKEN_INITIAL_SUPPLY" \
+    TRADE_DEADLINE="$TRADE_DEADLINE" "$@" \
+    cargo run -q --manifest-path scripts/sign_create_token/Cargo.toml | sed -n "s/^$key=//p"
+}
+
+# trade SIDE OWNER SECRET_HEX: signs (unless SIDE_SIG is set) and submits a 10-token trade.
+trade() {
+  local side="$1" owner="$2" secret="$3" mutation sig_var nonce sig
+  mutation=$(echo "$side" | tr '[:upper:]' '[:lower:]')
+  sig_var="${side}_SIG"
+  nonce="${TRADE_NONCE:-$(trade_nonce "$owner")}"
+  sig="${!sig_var:-$(sign "$owner" "$secret" "$sig_var" \
+    "${side}_AMOUNT=10" "${side}_MIN_OUT=1" "${side}_NONCE=$nonce")}"
+  curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
+    -H 'Content-Type: application/json' \
+    --data-raw "{\"query\":\"mutation { $mutation(trade: { payload: { owner: \\\"$owner\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: $side, amount: \\\"10\\\", minOut: \\\"1\\\", deadline: $TRADE_DEADLINE, nonce: $nonce }, signatureHex: \\\"$sig\\\" }) }\"}"
+}
+
 case "${1:-}" in
   user2-init)
     # --- Create user2 keypair (in-repo, independent of linera keygen) ---
@@ -53,7 +93,7 @@ case "${1:-}" in
     # --- Faucet mint wLin for user2 (signed by user2) ---
     curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$FAUCET_APP_ID" \
       -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { faucetMint(amount: \\\"1000\\\", owner: \\\"$OWNER_USER2\\\") }\"}"
+      --data-raw "{\"query\":\"mutation { faucetMint(request: { payload: { owner: \\\"$OWNER_USER2\\\", amount: \\\"1000\\\" }, signatureHex: \\\"${FAUCET_SIG_USER2:-}\\\" }) }\"}"
     ;;
   build)
     # --- Build/compile (WASM outputs) ---
@@ -93,9 +133,10 @@ case "${1:-}" in
     ;;
   wlin)
     # --- Create wLin fungible app (returns wLin app id) ---
+    # WLIN_WRAPPED_NATIVE=true backs wLin with native deposits (faucet minting disabled).
     linera --wallet "$WALLET" create-application \
       "$FUNGIBLE_MODULE_ID" \
-      --json-parameters "{\"ticker_symbol\":\"wLin\",\"minter_app_id\":\"$FAUCET_APP_ID\",\"trusted_caller_app_id\":\"$MATCHING_ENGINE_APP_ID\"}" \
+      --json-parameters "{\"ticker_symbol\":\"wLin\",\"minter_app_id\":\"$FAUCET_APP_ID\",\"trusted_caller_app_id\":\"$MATCHING_ENGINE_APP_ID\",\"wrapped_native\":${WLIN_WRAPPED_NATIVE:-false}}" \
       --json-argument '{"balances":[]}' \
       "$CHAIN_ID"
     ;;
@@ -117,6 +158,10 @@ case "${1:-}" in
       -H 'Content-Type: application/json' \
       --data-raw "{\"query\":\"mutation { setWlinApp(appId: \\\"$WLIN_APP_ID\\\") }\"}"
 
+    curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
+      -H 'Content-Type: application/json' \
+      --data-raw "{\"query\":\"mutation { setTokenFactory(appId: \\\"$TOKEN_FACTORY_APP_ID\\\") }\"}"
+
     curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
       -H 'Content-Type: application/json' \
       --data-raw "{\"query\":\"mutation { setFeeDestination(owner: \\\"$FEE_DESTINATION\\\") }\"}"
@@ -145,7 +190,7 @@ case "${1:-}" in
     # --- Faucet mint wLin (requires signer) ---
     curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$FAUCET_APP_ID" \
       -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { faucetMint(amount: \\\"1000\\\", owner: \\\"$OWNER\\\") }\"}"
+      --data-raw "{\"query\":\"mutation { faucetMint(request: { payload: { owner: \\\"$OWNER\\\", amount: \\\"1000\\\" }, signatureHex: \\\"${FAUCET_SIG:-}\\\" }) }\"}"
     ;;
   approve)
     # --- Approve wLin + TST for matching_engine ---
@@ -169,33 +214,28 @@ case "${1:-}" in
     ;;
   buy)
     # --- Buy TST ---
-    curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
-      -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { buy(trade: { payload: { owner: \\\"$OWNER\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: BUY, amount: \\\"10\\\", minOut: \\\"1\\\" }, signatureHex: \\\"$BUY_SIG\\\" }) }\"}"
+    trade BUY "$OWNER" "$SECRET_HEX"
     ;;
   buy-user2)
     # --- Buy TST (user2) ---
-    curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
-      -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { buy(trade: { payload: { owner: \\\"$OWNER_USER2\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: BUY, amount: \\\"10\\\", minOut: \\\"1\\\" }, signatureHex: \\\"$BUY_SIG_USER2\\\" }) }\"}"
+    trade BUY "$OWNER_USER2" "$SECRET_HEX_USER2"
     ;;
   sell)
     # --- Sell TST ---
-    curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
-      -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { sell(trade: { payload: { owner: \\\"$OWNER\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: SELL, amount: \\\"10\\\", minOut: \\\"1\\\" }, signatureHex: \\\"$SELL_SIG\\\" }) }\"}"
+    trade SELL "$OWNER" "$SECRET_HEX"
     ;;
   sell-user2)
     # --- Sell TST (user2) ---
-    curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
-      -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { sell(trade: { payload: { owner: \\\"$OWNER_USER2\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: SELL, amount: \\\"10\\\", minOut: \\\"1\\\" }, signatureHex: \\\"$SELL_SIG_USER2\\\" }) }\"}"
+    trade SELL "$OWNER_USER2" "$SECRET_HEX_USER2"
     ;;
   place-intent)
     # --- Place intent (escrow) for OWNER ---
+    NONCE="${TRADE_NONCE:-$(trade_nonce "$OWNER")}"
+    INTENT_SIG="${INTENT_SIG:-$(sign "$OWNER" "$SECRET_HEX" INTENT_SIG \
+      INTENT_SIDE=SELL INTENT_AMOUNT=10 INTENT_LIMIT_PRICE=0.001 INTENT_NONCE="$NONCE")}"
     curl -s "http://127.0.0.1:8080/chains/$CHAIN_ID/applications/$MATCHING_ENGINE_APP_ID" \
       -H 'Content-Type: application/json' \
-      --data-raw "{\"query\":\"mutation { placeIntent(intent: { payload: { owner: \\\"$OWNER\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: SELL, amount: \\\"10\\\", limitPrice: \\\"0.001\\\" }, signatureHex: \\\"$INTENT_SIG\\\" }) }\"}"
+      --data-raw "{\"query\":\"mutation { placeIntent(intent: { payload: { owner: \\\"$OWNER\\\", symbol: \\\"$TOKEN_SYMBOL\\\", side: SELL, amount: \\\"10\\\", limitPrice: \\\"0.001\\\", deadline: $TRADE_DEADLINE, nonce: $NONCE }, signatureHex: \\\"$INTENT_SIG\\\" }) }\"}"
     ;;
   settle-intent)
     # --- Settle intent (operator) ---
@@ -0,0 +1,197 @@
+#!/usr/bin/env python3
+"""Regenerates the EIP-712 known-answer vectors in intents-chain/auth/src/eip712.rs.
+
+Self-contained (keccak-256 and secp256k1 in pure Python) so it runs without
+ethers/viem or network access. It first checks itself against the `Mail`
+example of the EIP-712 specification, then prints the vectors.
+"""
+
+RC = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+]
+ROT = [[0, 36, 3, 41, 18], [1, 44, 10, 45, 2], [62, 6, 43, 15, 61], [28, 55, 25, 21, 56], [27, 20, 39, 8, 14]]
+MASK = (1 << 64) - 1
+
+
+def rol(x, n):
+    n %= 64
+    return ((x << n) | (x >> (64 - n))) & MASK if n else x
+
+
+def keccak_f(a):
+    for rc in RC:
+        c = [a[x][0] ^ a[x][1] ^ a[x][2] ^ a[x][3] ^ a[x][4] for x in range(5)]
+        d = [c[(x - 1) % 5] ^ rol(c[(x + 1) % 5], 1) for x in range(5)]
+        a = [[a[x][y] ^ d[x] for y in range(5)] for x in range(5)]
+        b = [[0] * 5 for _ in range(5)]
+        for x in range(5):
+            for y in range(5):
+                b[y][(2 * x + 3 * y) % 5] = rol(a[x][y], ROT[x][y])
+        a = [[b[x][y] ^ (~b[(x + 1) % 5][y] & b[(x + 2) % 5][y]) for y in range(5)] for x in range(5)]
+        a[0][0] ^= rc
+    return a
+
+
+def keccak(data: bytes) -> bytes:
+    rate = 136
+    padded = bytearray(data) + b"\x01"
+    while len(padded) % rate:
+        padded.append(0)
+    padded[-1] |= 0x80
+    state = [[0] * 5 for _ in range(5)]
+    for offset in range(0, len(padded), rate):
+        block = padded[offset:offset + rate]
+        for i in range(rate // 8):
+            state[i % 5][i // 5] ^= int.from_bytes(block[8 * i:8 * i + 8], "little")
+        state = keccak_f(state)
+    return b"".join(state[i % 5][i // 5].to_bytes(8, "little") for i in range(4))
+
+
+P = 2**256 - 2**32 - 977
+N = 0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141
+G = (
+    0x79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798,
+    0x483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8,
+)
+
+
+def point_add(a, b):
+    if a is None:
+        return b
+    if b is None:
+        return a
+    if a[0] == b[0] and (a[1] + b[1]) % P == 0:
+        return None
+    if a == b:
+        slope = 3 * a[0] * a[0] * pow(2 * a[1], -1, P) % P
+    else:
+        slope = (b[1] - a[1]) * pow(b[0] - a[0], -1, P) % P
+    x = (slope * slope - a[0] - b[0]) % P
+    return x, (slope * (a[0] - x) - a[1]) % P
+
+
+def point_mul(k, point):
+    result = None
+    while k:
+        if k & 1:
+            result = point_add(result, point)
+        point = point_add(point, point)
+        k >>= 1
+    return result
+
+
+def address(private_key):
+    x, y = point_mul(private_key, G)
+    return keccak(x.to_bytes(32, "big") + y.to_bytes(32, "big"))[12:]
+
+
+def sign(private_key, digest, k):
+    """Low-s `r || s || v` signature with an explicit nonce `k`."""
+    z = int.from_bytes(digest, "big")
+    r_point = point_mul(k, G)
+    r = r_point[0] % N
+    s = pow(k, -1, N) * (z + r * private_key) % N
+    v = r_point[1] & 1
+    if s > N // 2:
+        s, v = N - s, v ^ 1
+    return r.to_bytes(32, "big") + s.to_bytes(32, "big") + bytes([27 + v])
+
+
+def uint(value):
+    return value.to_bytes(32, "big")
+
+
+def string(value):
+    return keccak(value.encode())
+
+
+def hash_struct(type_, words):
+    return keccak(keccak(type_.encode()) + b"".join(words))
+
+
+def signing_hash(domain, struct):
+    return keccak(b"\x19\x01" + domain + struct)
+
+
+def check_spec_example():
+    person_t = "Person(string name,address wallet)"
+    mail_t = "Mail(Person from,Person to,string contents)" + person_t
+
+    def wallet(hex_):
+        return b"\x00" * 12 + bytes.fromhex(hex_)
+
+    cow = hash_struct(person_t, [string("Cow"), wallet("cd2a3d9f938e13cd947ec05abc7fe734df8dd826")])
+    bob = hash_struct(person_t, [string("Bob"), wallet("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb")])
+    mail = hash_struct(mail_t, [cow, bob, string("Hello, Bob!")])
+    assert mail.hex() == "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+    assert address(int.from_bytes(keccak(b"cow"), "big")).hex() == "cd2a3d9f938e13cd947ec05abc7fe734df8dd826"
+
+
+ONE = 10**18
+APPLICATION_ID = bytes.fromhex("0123456789abcdef" * 4)
+OWNER = "0x49c2f87001ec3e39ea5a4dbd115e404c4d4a4641e83c9a60dc3d9e77778f72c1"
+BENEFICIARY = "0x6e937d765dd86d832ccb5b0272a6d1297127d462bb98c6e57f8fb20954d6c4c4"
+
+TRADE_T = (
+    "TradeRequest(string owner,string symbol,string side,uint256 amount,uint256 minOut,"
+    "uint256 relayerFee,uint64 deadline,uint64 nonce)"
+)
+METADATA_T = "TokenMetadata(string name,string symbol,uint8 decimals)"
+DEV_BUY_T = "DevBuy(uint256 amount,uint256 minOut)"
+ALLOCATION_T = "Allocation(string beneficiary,uint256 amount,uint64 cliffSecs,uint64 vestingSecs)"
+CREATE_TOKEN_T = (
+    "CreateTokenRequest(string owner,TokenMetadata metadata,uint256 initialSupply,"
+    "bool hasDevBuy,DevBuy devBuy,Allocation[] allocations)"
+    + ALLOCATION_T + DEV_BUY_T + METADATA_T
+)
+
+
+def create_token(dev_buy, allocations):
+    """`dev_buy` is `None` or `(amount, min_out)`; allocations are struct hashes with amounts."""
+    metadata = hash_struct(METADATA_T, [string("Test"), string("TST"), uint(18)])
+    amount, min_out = dev_buy or (0, 0)
+    supply = 800_000_000 * ONE + sum(allocated for _, allocated in allocations)
+    return hash_struct(CREATE_TOKEN_T, [
+        string(OWNER),
+        metadata,
+        uint(supply),
+        uint(0 if dev_buy is None else 1),
+        hash_struct(DEV_BUY_T, [uint(amount), uint(min_out)]),
+        keccak(b"".join(struct for struct, _ in allocations)),
+    ])
+
+
+def main():
+    check_spec_example()
+    domain = hash_struct(
+        "EIP712Domain(string name,string version,bytes32 salt)",
+        [string("IntentsChain"), string("1"), APPLICATION_ID],
+    )
+    print("domain_separator", domain.hex())
+
+    trade = hash_struct(TRADE_T, [
+        string(OWNER), string("TST"), string("BUY"), uint(10 * ONE), uint(15 * ONE // 10),
+        uint(ONE // 100), uint(1_700_000_000_000_000), uint(7),
+    ])
+    trade_digest = signing_hash(domain, trade)
+    print("trade hash_struct", trade.hex())
+    print("trade signing_hash", trade_digest.hex())
+
+    allocation = hash_struct(ALLOCATION_T, [string(BENEFICIARY), uint(1_000_000 * ONE), uint(86_400), uint(2_592_000)])
+    full = create_token((5 * ONE, 0), [(allocation, 1_000_000 * ONE)])
+    print("create_token signing_hash", signing_hash(domain, full).hex())
+    print("create_token (no extras) signing_hash", signing_hash(domain, create_token(None, [])).hex())
+    print("create_token (zero dev buy) signing_hash", signing_hash(domain, create_token((0, 0), [])).hex())
+
+    cow = int.from_bytes(keccak(b"cow"), "big")
+    k = 0x1234567890ABCDEF1234567890ABCDEF1234567890ABCDEF1234567890ABCDEF
+    print("trade signature (cow key)", sign(cow, trade_digest, k).hex())
+
+
+if __name__ == "__main__":
+    main()
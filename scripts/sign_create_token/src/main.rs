@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result};
 use linera_base::crypto::{AccountSecretKey, BcsSignable, Ed25519SecretKey};
-use shared_types::{CreateTokenRequest, Side, TokenMetadata, TradeRequest};
+use shared_types::{CreateTokenRequest, DevBuy, Intent, Side, TokenMetadata, TradeRequest};
 use std::env;
 use std::str::FromStr;
 
@@ -32,6 +32,13 @@ fn main() -> Result<()> {
 
     let owner = linera_base::identifiers::AccountOwner::from_str(&owner)?;
     let initial_supply = linera_base::data_types::Amount::from_str(&initial_supply)?;
+    let dev_buy = match (optional_env("DEV_BUY_AMOUNT"), optional_env("DEV_BUY_MIN_OUT")) {
+        (Some(amount), Some(min_out)) => Some(DevBuy {
+            amount: linera_base::data_types::Amount::from_str(&amount)?,
+            min_out: linera_base::data_types::Amount::from_str(&min_out)?,
+        }),
+        _ => None,
+    };
 
     let payload = CreateTokenRequest {
         owner,
@@ -41,6 +48,8 @@ fn main() -> Result<()> {
             decimals,
         },
         initial_supply,
+        dev_buy,
+        allocations: vec![],
     };
 
     // Ed25519 secret key is serialized as a hex string.
@@ -82,7 +91,34 @@ fn main() -> Result<()> {
         println!("TST_APPROVE_SIG={}", sig);
     }
 
+    // Optional: faucet claim signature (faucet).
+    if let Some(faucet_amount) = optional_env("FAUCET_AMOUNT") {
+        let faucet_payload = faucet::FaucetMintRequest {
+            owner: owner.clone(),
+            amount: linera_base::data_types::Amount::from_str(&faucet_amount)?,
+        };
+        let sig = sign_and_hex(&secret, &faucet_payload)?;
+        println!("FAUCET_SIG={}", sig);
+    }
+
     // Optional: Buy/Sell trade signatures (matching_engine).
+    let relayer_fee = match optional_env("RELAYER_FEE") {
+        Some(fee) => linera_base::data_types::Amount::from_str(&fee)?,
+        None => linera_base::data_types::Amount::ZERO,
+    };
+    // Signed trades expire (default: one hour from now) and consume the
+    // owner's trade nonce (see the `tradeNonce` query); buy then sell by default.
+    let deadline_micros = match optional_env("TRADE_DEADLINE") {
+        Some(deadline) => deadline.parse()?,
+        None => {
+            let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+            u64::try_from(now.as_micros())? + 3_600_000_000
+        }
+    };
+    let deadline = linera_base::data_types::Timestamp::from(deadline_micros);
+    let buy_nonce: u64 = optional_env("BUY_NONCE").map_or(Ok(0), |n| n.parse())?;
+    let sell_nonce: u64 = optional_env("SELL_NONCE").map_or(Ok(1), |n| n.parse())?;
+    println!("TRADE_DEADLINE={}", deadline_micros);
     if let (Some(buy_amount), Some(buy_min_out)) =
         (optional_env("BUY_AMOUNT"), optional_env("BUY_MIN_OUT"))
     {
@@ -94,6 +130,9 @@ fn main() -> Result<()> {
             side: Side::Buy,
             amount,
             min_out,
+            relayer_fee,
+            deadline,
+            nonce: buy_nonce,
         };
         let sig = sign_and_hex(&secret, &buy_payload)?;
         println!("BUY_SIG={}", sig);
@@ -105,14 +144,39 @@ fn main() -> Result<()> {
         let amount = linera_base::data_types::Amount::from_str(&sell_amount)?;
         let min_out = linera_base::data_types::Amount::from_str(&sell_min_out)?;
         let sell_payload = TradeRequest {
-            owner,
+            owner: owner.clone(),
             symbol: symbol.clone(),
             side: Side::Sell,
             amount,
             min_out,
+            relayer_fee,
+            deadline,
+            nonce: sell_nonce,
         };
         let sig = sign_and_hex(&secret, &sell_payload)?;
         println!("SELL_SIG={}", sig);
     }
+
+    // Optional: intent signature; intents share the trade deadline and nonce counter.
+    if let (Some(intent_amount), Some(limit_price)) =
+        (optional_env("INTENT_AMOUNT"), optional_env("INTENT_LIMIT_PRICE"))
+    {
+        let side = match optional_env("INTENT_SIDE").as_deref() {
+            Some("BUY") => Side::Buy,
+            _ => Side::Sell,
+        };
+        let intent_payload = Intent {
+            owner,
+            symbol,
+            side,
+            amount: linera_base::data_types::Amount::from_str(&intent_amount)?,
+            limit_price: limit_price.parse()?,
+            relayer_fee,
+            deadline,
+            nonce: optional_env("INTENT_NONCE").map_or(Ok(0), |n| n.parse())?,
+        };
+        let sig = sign_and_hex(&secret, &intent_payload)?;
+        println!("INTENT_SIG={}", sig);
+    }
     Ok(())
 }